@@ -0,0 +1,45 @@
+use nomad_client::client::NomadClient;
+use nomad_client::debug::{capture, DebugOptions};
+
+use std::fs::File;
+use std::time::Duration;
+
+//
+// Capture a debug bundle from the cluster in NOMAD_ADDR
+//
+// usage: debug [duration-secs] [server,...] [node-id,...]
+//
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let mut options = DebugOptions::default();
+    if let Some(secs) = args.next() {
+        options.duration = Duration::from_secs(secs.parse()?);
+    }
+    if let Some(servers) = args.next() {
+        options.servers = servers.split(',').map(String::from).collect();
+    }
+    if let Some(nodes) = args.next() {
+        options.nodes = nodes.split(',').map(String::from).collect();
+    }
+
+    let path = format!(
+        "nomad-debug-{}.tar.gz",
+        chrono::Utc::now().format("%Y-%m-%d-%H%M%SZ")
+    );
+    let file = File::create(&path)?;
+    println!(
+        "Capturing for {}s, servers {:?}, nodes {:?}",
+        options.duration.as_secs(),
+        options.servers,
+        options.nodes
+    );
+
+    let client = NomadClient::from_env();
+    let manifest = capture(&client, &options, file).await?;
+    println!("Wrote {} files to {}", manifest.files.len(), path);
+    for error in manifest.errors.iter() {
+        println!("  failed {}: {}", error.path, error.error);
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@ use futures_util::StreamExt;
 use nomad_client::chunked_response::Assembler;
 use nomad_client::model::event_stream::Events;
 
+#[allow(clippy::needless_borrow)]
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let response = reqwest::get("http://127.0.0.1:4646/v1/event/stream").await?;
@@ -0,0 +1,25 @@
+use nomad_client::client::jobs::RunOptions;
+use nomad_client::client::NomadClient;
+use nomad_client::model::jobs::JobSpec;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).expect("usage: run <job.json>");
+    let spec: JobSpec = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    let client = NomadClient::from_env();
+    let report = client.jobs().run(spec.job, RunOptions::default()).await?;
+
+    println!("Evaluation ID: {}", report.register.eval_id);
+    if let Some(ref evaluation) = report.evaluation {
+        let failures = evaluation.explain_failures();
+        if !failures.is_empty() {
+            println!("{}", failures);
+        }
+    }
+    if let Some(ref deployment) = report.deployment {
+        println!("Deployment {} {}", deployment.id, deployment.status);
+    }
+    println!("Success: {}", report.is_success());
+    Ok(())
+}
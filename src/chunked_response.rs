@@ -6,10 +6,12 @@ pub struct Assembler {
 }
 
 impl Assembler {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self { buffered: None }
     }
 
+    #[allow(clippy::needless_borrow)]
     pub fn add<T>(&mut self, chunk: &str) -> Result<Option<T>, serde_json::error::Error>
     where
         T: DeserializeOwned,
@@ -42,6 +44,7 @@ impl Assembler {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use super::*;
     use std::collections::HashMap;
@@ -0,0 +1,540 @@
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+pub mod acl;
+pub mod agent;
+pub mod allocations;
+pub mod csi;
+pub mod deployments;
+pub mod evaluations;
+pub mod jobs;
+pub mod monitor;
+pub mod namespaces;
+pub mod operator;
+pub mod scaling;
+pub mod search;
+pub mod services;
+pub mod status;
+pub mod variables;
+
+#[cfg(test)]
+pub(crate) mod stub_server;
+
+pub const DEFAULT_ADDRESS: &str = "http://127.0.0.1:4646";
+
+// QueryOptions are the parameters shared by all read requests. Blocking
+// queries are performed by setting `wait_index` to the `last_index` of a
+// previous response.
+#[derive(Debug, Default, Clone)]
+pub struct QueryOptions {
+    pub region: Option<String>,
+    pub namespace: Option<String>,
+    pub prefix: Option<String>,
+    pub filter: Option<String>,
+    pub per_page: Option<u32>,
+    pub next_token: Option<String>,
+    pub wait_index: Option<u64>,
+    pub wait_time: Option<Duration>,
+}
+
+impl QueryOptions {
+    fn to_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        if let Some(ref region) = self.region {
+            pairs.push(("region", region.clone()));
+        }
+        if let Some(ref namespace) = self.namespace {
+            pairs.push(("namespace", namespace.clone()));
+        }
+        if let Some(ref prefix) = self.prefix {
+            pairs.push(("prefix", prefix.clone()));
+        }
+        if let Some(ref filter) = self.filter {
+            pairs.push(("filter", filter.clone()));
+        }
+        if let Some(per_page) = self.per_page {
+            pairs.push(("per_page", per_page.to_string()));
+        }
+        if let Some(ref next_token) = self.next_token {
+            pairs.push(("next_token", next_token.clone()));
+        }
+        if let Some(index) = self.wait_index {
+            pairs.push(("index", index.to_string()));
+        }
+        if let Some(wait) = self.wait_time {
+            pairs.push(("wait", format!("{}ms", wait.as_millis())));
+        }
+        pairs
+    }
+}
+
+// QueryMeta is populated from the X-Nomad-* headers of a read response
+#[derive(Debug, Default, Clone)]
+pub struct QueryMeta {
+    pub last_index: u64,
+    pub known_leader: bool,
+    pub last_contact: Duration,
+    pub next_token: Option<String>,
+}
+
+impl QueryMeta {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        QueryMeta {
+            last_index: header("X-Nomad-Index")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            known_leader: header("X-Nomad-KnownLeader") == Some("true"),
+            last_contact: header("X-Nomad-LastContact")
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or_default(),
+            next_token: header("X-Nomad-NextToken")
+                .filter(|v| !v.is_empty())
+                .map(String::from),
+        }
+    }
+}
+
+// WriteMeta is populated from the headers of a write response
+#[derive(Debug, Default, Clone)]
+pub struct WriteMeta {
+    pub last_index: u64,
+}
+
+#[derive(Clone)]
+pub struct NomadClient {
+    http: reqwest::Client,
+    address: String,
+    token: Option<String>,
+    region: Option<String>,
+    namespace: Option<String>,
+}
+
+impl fmt::Debug for NomadClient {
+    // Deliberately omits the token
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NomadClient")
+            .field("address", &self.address)
+            .field("region", &self.region)
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+impl Default for NomadClient {
+    fn default() -> Self {
+        NomadClient::new(DEFAULT_ADDRESS)
+    }
+}
+
+impl NomadClient {
+    pub fn new(address: &str) -> Self {
+        NomadClient {
+            http: reqwest::Client::new(),
+            address: address.trim_end_matches('/').to_string(),
+            token: None,
+            region: None,
+            namespace: None,
+        }
+    }
+
+    //
+    // Construct a client using the same environment variables as the nomad
+    // cli; NOMAD_ADDR, NOMAD_TOKEN, NOMAD_REGION and NOMAD_NAMESPACE
+    //
+    pub fn from_env() -> Self {
+        let address = std::env::var("NOMAD_ADDR").unwrap_or_else(|_| DEFAULT_ADDRESS.to_string());
+        let mut client = NomadClient::new(&address);
+        client.token = std::env::var("NOMAD_TOKEN").ok().filter(|t| !t.is_empty());
+        client.region = std::env::var("NOMAD_REGION").ok().filter(|r| !r.is_empty());
+        client.namespace = std::env::var("NOMAD_NAMESPACE")
+            .ok()
+            .filter(|n| !n.is_empty());
+        client
+    }
+
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub fn with_region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+
+    //
+    // Scope requests to a namespace, `ALL_NAMESPACES` lists across every
+    // namespace. A namespace given in `QueryOptions` takes precedence.
+    //
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    pub fn acl(&self) -> acl::Acl<'_> {
+        acl::Acl::new(self)
+    }
+
+    pub fn agent(&self) -> agent::Agent<'_> {
+        agent::Agent::new(self)
+    }
+
+    pub fn allocations(&self) -> allocations::Allocations<'_> {
+        allocations::Allocations::new(self)
+    }
+
+    pub fn csi_plugins(&self) -> csi::CSIPlugins<'_> {
+        csi::CSIPlugins::new(self)
+    }
+
+    pub fn csi_volumes(&self) -> csi::CSIVolumes<'_> {
+        csi::CSIVolumes::new(self)
+    }
+
+    pub fn deployments(&self) -> deployments::Deployments<'_> {
+        deployments::Deployments::new(self)
+    }
+
+    pub fn evaluations(&self) -> evaluations::Evaluations<'_> {
+        evaluations::Evaluations::new(self)
+    }
+
+    pub fn jobs(&self) -> jobs::Jobs<'_> {
+        jobs::Jobs::new(self)
+    }
+
+    pub fn namespaces(&self) -> namespaces::Namespaces<'_> {
+        namespaces::Namespaces::new(self)
+    }
+
+    pub fn operator(&self) -> operator::Operator<'_> {
+        operator::Operator::new(self)
+    }
+
+    pub fn scaling(&self) -> scaling::Scaling<'_> {
+        scaling::Scaling::new(self)
+    }
+
+    pub fn search(&self) -> search::Search<'_> {
+        search::Search::new(self)
+    }
+
+    pub fn services(&self) -> services::Services<'_> {
+        services::Services::new(self)
+    }
+
+    pub fn status(&self) -> status::Status<'_> {
+        status::Status::new(self)
+    }
+
+    pub fn variables(&self) -> variables::Variables<'_> {
+        variables::Variables::new(self)
+    }
+
+    //
+    // Build a request with the given query parameters, adding the client's
+    // region and namespace unless the parameters already specify them
+    //
+    pub(crate) fn request(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> RequestBuilder {
+        let mut builder = self
+            .http
+            .request(method, format!("{}{}", self.address, path))
+            .query(params);
+        if let Some(ref token) = self.token {
+            builder = builder.header("X-Nomad-Token", token);
+        }
+        for (key, value) in [("region", &self.region), ("namespace", &self.namespace)] {
+            if let Some(value) = value {
+                if !params.iter().any(|(k, _)| *k == key) {
+                    builder = builder.query(&[(key, value)]);
+                }
+            }
+        }
+        builder
+    }
+
+    pub(crate) async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+        check(builder.send().await?).await
+    }
+
+    //
+    // Perform a GET request returning the decoded body and the query metadata
+    //
+    pub(crate) async fn query<T>(
+        &self,
+        path: &str,
+        options: &QueryOptions,
+    ) -> Result<(T, QueryMeta)>
+    where
+        T: DeserializeOwned,
+    {
+        self.query_with(path, &[], options).await
+    }
+
+    pub(crate) async fn query_with<T>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+        options: &QueryOptions,
+    ) -> Result<(T, QueryMeta)>
+    where
+        T: DeserializeOwned,
+    {
+        let mut pairs = options.to_pairs();
+        pairs.extend(params.iter().map(|(k, v)| (*k, v.clone())));
+        let builder = self.request(Method::GET, path, &pairs);
+        let response = self.send(builder).await?;
+        let meta = QueryMeta::from_headers(response.headers());
+        let bytes = response.bytes().await?;
+        Ok((serde_json::from_slice(&bytes)?, meta))
+    }
+
+    //
+    // Perform a read which takes a JSON body, such as a search, returning the
+    // decoded body and the query metadata
+    //
+    pub(crate) async fn query_body<B, T>(
+        &self,
+        path: &str,
+        body: &B,
+        options: &QueryOptions,
+    ) -> Result<(T, QueryMeta)>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let builder = self
+            .request(Method::POST, path, &options.to_pairs())
+            .json(body);
+        let response = self.send(builder).await?;
+        let meta = QueryMeta::from_headers(response.headers());
+        let bytes = response.bytes().await?;
+        Ok((serde_json::from_slice(&bytes)?, meta))
+    }
+
+    //
+    // Perform a GET request returning the raw body, for endpoints which do
+    // not return JSON such as profiles and snapshots
+    //
+    pub(crate) async fn query_bytes(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<(Bytes, QueryMeta)> {
+        let builder = self.request(Method::GET, path, params);
+        let response = self.send(builder).await?;
+        let meta = QueryMeta::from_headers(response.headers());
+        Ok((response.bytes().await?, meta))
+    }
+
+    //
+    // Perform a write (PUT, POST or DELETE) with an optional JSON body
+    //
+    pub(crate) async fn write<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<T>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let (value, _) = self.write_with_meta(method, path, params, body).await?;
+        Ok(value)
+    }
+
+    //
+    // As `write` but also returning the index of the write. An empty response
+    // body is decoded as `null` so endpoints without a body can use `()`.
+    //
+    pub(crate) async fn write_with_meta<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<(T, WriteMeta)>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let response = self
+            .send(self.write_request(method, path, params, body))
+            .await?;
+        decode_write(response).await
+    }
+
+    //
+    // As `write_with_meta` for check-and-set writes, which the server rejects
+    // with 409 and the object it currently holds. That body is returned as
+    // `Err` for the caller to decode rather than failing the request.
+    //
+    pub(crate) async fn write_cas<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<std::result::Result<(T, WriteMeta), Bytes>>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let response = self
+            .write_request(method, path, params, body)
+            .send()
+            .await?;
+        if response.status() == StatusCode::CONFLICT {
+            return Ok(Err(response.bytes().await?));
+        }
+        Ok(Ok(decode_write(check(response).await?).await?))
+    }
+
+    fn write_request<B>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+        body: Option<&B>,
+    ) -> RequestBuilder
+    where
+        B: Serialize + ?Sized,
+    {
+        let builder = self.request(method, path, params);
+        match body {
+            Some(body) => builder.json(body),
+            None => builder,
+        }
+    }
+}
+
+// Fail a response with a non-success status with its body as the message
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(Error::Api {
+            status: status.as_u16(),
+            message: message.trim().to_string(),
+        });
+    }
+    Ok(response)
+}
+
+// An empty response body is decoded as `null` so endpoints without a body
+// can use `()`
+async fn decode_write<T: DeserializeOwned>(response: Response) -> Result<(T, WriteMeta)> {
+    let meta = WriteMeta {
+        last_index: QueryMeta::from_headers(response.headers()).last_index,
+    };
+    let bytes = response.bytes().await?;
+    let bytes: &[u8] = if bytes.iter().all(u8::is_ascii_whitespace) {
+        b"null"
+    } else {
+        &bytes
+    };
+    Ok((serde_json::from_slice(bytes)?, meta))
+}
+
+//
+// Percent-encode a caller supplied value for use as one segment of a request
+// path, as go's url.PathEscape does. IDs of dispatched and periodic jobs hold
+// a '/' and names may hold characters such as '?', '#' or spaces.
+//
+pub(crate) fn segment(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => out.push(char::from(b)),
+            b'-' | b'.' | b'_' | b'~' | b'$' | b'&' | b'+' | b',' | b':' | b';' | b'=' | b'@' => {
+                out.push(char::from(b))
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_path_segments() {
+        assert_eq!(segment("example"), "example");
+        assert_eq!(
+            segment("batch/dispatch-1485408778-81644024"),
+            "batch%2Fdispatch-1485408778-81644024"
+        );
+        assert_eq!(segment("a b?c#d%"), "a%20b%3Fc%23d%25");
+        assert_eq!(segment("caf\u{e9}"), "caf%C3%A9");
+    }
+
+    #[test]
+    fn query_options_pairs() {
+        let options = QueryOptions {
+            prefix: Some("f623".to_string()),
+            per_page: Some(10),
+            wait_index: Some(33),
+            wait_time: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let pairs = options.to_pairs();
+        assert!(pairs.contains(&("prefix", "f623".to_string())));
+        assert!(pairs.contains(&("per_page", "10".to_string())));
+        assert!(pairs.contains(&("index", "33".to_string())));
+        assert!(pairs.contains(&("wait", "5000ms".to_string())));
+    }
+
+    #[test]
+    fn query_meta_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Nomad-Index", "35".parse().unwrap());
+        headers.insert("X-Nomad-KnownLeader", "true".parse().unwrap());
+        headers.insert("X-Nomad-LastContact", "12".parse().unwrap());
+        headers.insert("X-Nomad-NextToken", "".parse().unwrap());
+
+        let meta = QueryMeta::from_headers(&headers);
+        assert_eq!(meta.last_index, 35);
+        assert!(meta.known_leader);
+        assert_eq!(meta.last_contact, Duration::from_millis(12));
+        assert!(meta.next_token.is_none());
+    }
+
+    #[test]
+    fn debug_omits_token() {
+        let client = NomadClient::new("http://nomad.local:4646/").with_token("s3cr3t");
+        let output = format!("{:?}", client);
+        assert!(!output.contains("s3cr3t"));
+        assert_eq!(client.address(), "http://nomad.local:4646");
+    }
+}
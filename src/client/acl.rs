@@ -0,0 +1,342 @@
+use reqwest::Method;
+
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions, WriteMeta};
+use crate::error::{Error, Result};
+use crate::model::acl::{
+    ACLPolicy, ACLPolicyListStub, ACLRole, ACLRoleListStub, ACLToken, ACLTokenListStub,
+    BootstrapRequest, OneTimeToken, OneTimeTokenExchangeRequest, OneTimeTokenExchangeResponse,
+    OneTimeTokenUpsertResponse,
+};
+
+pub struct Acl<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Acl<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Acl { client }
+    }
+
+    //
+    // Bootstrap the ACL system returning the initial management token. An
+    // operator supplied `bootstrap_secret` becomes the token's secret.
+    //
+    pub async fn bootstrap(&self, bootstrap_secret: Option<&str>) -> Result<ACLToken> {
+        let request = BootstrapRequest {
+            bootstrap_secret: bootstrap_secret.unwrap_or_default().to_string(),
+        };
+        self.client
+            .write(Method::POST, "/v1/acl/bootstrap", &[], Some(&request))
+            .await
+    }
+
+    //
+    // Tokens
+    //
+
+    pub async fn tokens(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(Vec<ACLTokenListStub>, QueryMeta)> {
+        self.client.query("/v1/acl/tokens", options).await
+    }
+
+    pub async fn token(
+        &self,
+        accessor_id: &str,
+        options: &QueryOptions,
+    ) -> Result<(ACLToken, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/acl/token/{}", segment(accessor_id)), options)
+            .await
+    }
+
+    // The token the client is authenticating with
+    pub async fn token_self(&self, options: &QueryOptions) -> Result<(ACLToken, QueryMeta)> {
+        self.client.query("/v1/acl/token/self", options).await
+    }
+
+    pub async fn create_token(&self, token: &ACLToken) -> Result<ACLToken> {
+        if !token.accessor_id.is_empty() {
+            return Err(Error::InvalidRequest(
+                "cannot specify accessor ID when creating a token".to_string(),
+            ));
+        }
+        self.client
+            .write(Method::POST, "/v1/acl/token", &[], Some(token))
+            .await
+    }
+
+    pub async fn update_token(&self, token: &ACLToken) -> Result<ACLToken> {
+        if token.accessor_id.is_empty() {
+            return Err(Error::InvalidRequest(
+                "must specify accessor ID when updating a token".to_string(),
+            ));
+        }
+        self.client
+            .write(
+                Method::POST,
+                &format!("/v1/acl/token/{}", segment(&token.accessor_id)),
+                &[],
+                Some(token),
+            )
+            .await
+    }
+
+    pub async fn delete_token(&self, accessor_id: &str) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/acl/token/{}", segment(accessor_id)),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    // Create a one-time token for the token the client is authenticating with
+    pub async fn create_one_time_token(&self) -> Result<OneTimeToken> {
+        let response: OneTimeTokenUpsertResponse = self
+            .client
+            .write::<(), _>(Method::POST, "/v1/acl/token/onetime", &[], None)
+            .await?;
+        response
+            .one_time_token
+            .ok_or_else(|| Error::UnexpectedResponse("no one-time token returned".to_string()))
+    }
+
+    pub async fn exchange_one_time_token(&self, one_time_secret_id: &str) -> Result<ACLToken> {
+        let request = OneTimeTokenExchangeRequest {
+            one_time_secret_id: one_time_secret_id.to_string(),
+        };
+        let response: OneTimeTokenExchangeResponse = self
+            .client
+            .write(
+                Method::POST,
+                "/v1/acl/token/onetime/exchange",
+                &[],
+                Some(&request),
+            )
+            .await?;
+        response
+            .token
+            .ok_or_else(|| Error::UnexpectedResponse("no token returned from exchange".to_string()))
+    }
+
+    //
+    // Policies
+    //
+
+    pub async fn policies(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(Vec<ACLPolicyListStub>, QueryMeta)> {
+        self.client.query("/v1/acl/policies", options).await
+    }
+
+    pub async fn policy(
+        &self,
+        name: &str,
+        options: &QueryOptions,
+    ) -> Result<(ACLPolicy, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/acl/policy/{}", segment(name)), options)
+            .await
+    }
+
+    // Create the policy or update it if it already exists
+    pub async fn upsert_policy(&self, policy: &ACLPolicy) -> Result<WriteMeta> {
+        if policy.name.is_empty() {
+            return Err(Error::InvalidRequest("missing policy name".to_string()));
+        }
+        let ((), meta) = self
+            .client
+            .write_with_meta(
+                Method::POST,
+                &format!("/v1/acl/policy/{}", segment(&policy.name)),
+                &[],
+                Some(policy),
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    pub async fn delete_policy(&self, name: &str) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/acl/policy/{}", segment(name)),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    //
+    // Roles
+    //
+
+    pub async fn roles(&self, options: &QueryOptions) -> Result<(Vec<ACLRoleListStub>, QueryMeta)> {
+        self.client.query("/v1/acl/roles", options).await
+    }
+
+    pub async fn role(&self, id: &str, options: &QueryOptions) -> Result<(ACLRole, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/acl/role/{}", segment(id)), options)
+            .await
+    }
+
+    pub async fn role_by_name(
+        &self,
+        name: &str,
+        options: &QueryOptions,
+    ) -> Result<(ACLRole, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/acl/role/name/{}", segment(name)), options)
+            .await
+    }
+
+    pub async fn create_role(&self, role: &ACLRole) -> Result<ACLRole> {
+        if !role.id.is_empty() {
+            return Err(Error::InvalidRequest(
+                "cannot specify ID when creating a role".to_string(),
+            ));
+        }
+        self.client
+            .write(Method::POST, "/v1/acl/role", &[], Some(role))
+            .await
+    }
+
+    pub async fn update_role(&self, role: &ACLRole) -> Result<ACLRole> {
+        if role.id.is_empty() {
+            return Err(Error::InvalidRequest(
+                "must specify ID when updating a role".to_string(),
+            ));
+        }
+        self.client
+            .write(
+                Method::POST,
+                &format!("/v1/acl/role/{}", segment(&role.id)),
+                &[],
+                Some(role),
+            )
+            .await
+    }
+
+    pub async fn delete_role(&self, id: &str) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/acl/role/{}", segment(id)),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    use std::time::Duration;
+
+    fn token_json(accessor: &str) -> serde_json::Value {
+        json!({
+            "AccessorID": accessor,
+            "SecretID": "3f4a0fcd-7c42-773c-25db-2d31ba0c05fe",
+            "Name": "team-web",
+            "Type": "client",
+            "Policies": ["team-web"],
+            "Global": false,
+            "CreateTime": "2017-08-23T22:47:14.695408057Z",
+            "CreateIndex": 7,
+            "ModifyIndex": 7
+        })
+    }
+
+    #[tokio::test]
+    async fn create_and_exchange_tokens() {
+        let address = serve(
+            |request| match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/v1/acl/token") => {
+                    let body: serde_json::Value =
+                        serde_json::from_slice(&request.body).expect("token body");
+                    assert!(body.get("AccessorID").is_none());
+                    assert!(body.get("SecretID").is_none());
+                    assert_eq!(body["Policies"], json!(["team-web"]));
+                    assert_eq!(body["ExpirationTTL"], json!(3_600_000_000_000u64));
+                    StubResponse::json(&token_json("b780e702"))
+                }
+                ("POST", "/v1/acl/token/onetime/exchange") => {
+                    let body: serde_json::Value =
+                        serde_json::from_slice(&request.body).expect("exchange body");
+                    assert_eq!(body["OneTimeSecretID"], json!("one-time"));
+                    StubResponse::json(&json!({"Token": token_json("c1")}))
+                }
+                ("POST", "/v1/acl/token/onetime") => StubResponse::json(&json!({"Index": 9})),
+                ("GET", "/v1/acl/token/self") => StubResponse::json(&token_json("self")),
+                _ => StubResponse::raw(404, b"not found"),
+            },
+        )
+        .await;
+
+        let client = NomadClient::new(&address);
+        let token = ACLToken {
+            name: "team-web".to_string(),
+            token_type: "client".to_string(),
+            policies: vec!["team-web".to_string()],
+            expiration_ttl: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let created = client.acl().create_token(&token).await.expect("create");
+        assert_eq!(created.accessor_id, "b780e702");
+
+        let exchanged = client
+            .acl()
+            .exchange_one_time_token("one-time")
+            .await
+            .expect("exchange");
+        assert_eq!(exchanged.accessor_id, "c1");
+
+        let result = client.acl().create_one_time_token().await;
+        assert!(matches!(result, Err(Error::UnexpectedResponse(_))));
+
+        let (current, _) = client
+            .acl()
+            .token_self(&QueryOptions::default())
+            .await
+            .expect("self");
+        assert_eq!(current.accessor_id, "self");
+
+        let result = client.acl().update_token(&ACLToken::default()).await;
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn upsert_policy() {
+        let address = serve(|request| {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/v1/acl/policy/team-web");
+            StubResponse::raw(200, b"").index(12)
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let policy = ACLPolicy {
+            name: "team-web".to_string(),
+            rules: "namespace \"web\" { policy = \"write\" }".to_string(),
+            ..Default::default()
+        };
+        let meta = client.acl().upsert_policy(&policy).await.expect("upsert");
+        assert_eq!(meta.last_index, 12);
+    }
+}
@@ -0,0 +1,453 @@
+use bytes::Bytes;
+use reqwest::{Method, Response, StatusCode};
+
+use std::collections::VecDeque;
+
+use crate::chunked_response::Assembler;
+use crate::client::{NomadClient, QueryMeta, QueryOptions};
+use crate::error::{Error, Result};
+use crate::model::agent::{
+    AgentHealthResponse, AgentSelf, JoinResponse, LogEntry, ServerMembers, StreamFrame,
+};
+
+//
+// Options for streaming agent logs. Without a `node_id` or `server_id` the
+// logs of the agent the client talks to are streamed.
+//
+#[derive(Debug, Clone)]
+pub struct LogMonitorOptions {
+    pub log_level: String,
+    pub node_id: Option<String>,
+    pub server_id: Option<String>,
+    pub log_json: bool,
+}
+
+impl Default for LogMonitorOptions {
+    fn default() -> Self {
+        LogMonitorOptions {
+            log_level: "info".to_string(),
+            node_id: None,
+            server_id: None,
+            log_json: false,
+        }
+    }
+}
+
+impl LogMonitorOptions {
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("log_level", self.log_level.clone())];
+        if let Some(ref node_id) = self.node_id {
+            params.push(("node_id", node_id.clone()));
+        }
+        if let Some(ref server_id) = self.server_id {
+            params.push(("server_id", server_id.clone()));
+        }
+        if self.log_json {
+            params.push(("log_json", "true".to_string()));
+        }
+        params
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Cpu,
+    Heap,
+    Goroutine,
+    Trace,
+}
+
+impl Profile {
+    fn path(&self) -> &'static str {
+        match self {
+            Profile::Cpu => "/v1/agent/pprof/profile",
+            Profile::Heap => "/v1/agent/pprof/heap",
+            Profile::Goroutine => "/v1/agent/pprof/goroutine",
+            Profile::Trace => "/v1/agent/pprof/trace",
+        }
+    }
+}
+
+//
+// Options for capturing a profile. `seconds` applies to the cpu profile and
+// trace, `debug` selects the text format of the heap and goroutine profiles
+// and `gc` runs a collection before capturing the heap.
+//
+#[derive(Debug, Default, Clone)]
+pub struct ProfileOptions {
+    pub node_id: Option<String>,
+    pub server_id: Option<String>,
+    pub seconds: Option<u32>,
+    pub debug: Option<u32>,
+    pub gc: bool,
+}
+
+impl ProfileOptions {
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(ref node_id) = self.node_id {
+            params.push(("node_id", node_id.clone()));
+        }
+        if let Some(ref server_id) = self.server_id {
+            params.push(("server_id", server_id.clone()));
+        }
+        if let Some(seconds) = self.seconds {
+            params.push(("seconds", seconds.to_string()));
+        }
+        if let Some(debug) = self.debug {
+            params.push(("debug", debug.to_string()));
+        }
+        if self.gc {
+            params.push(("gc", "1".to_string()));
+        }
+        params
+    }
+}
+
+//
+// LogStream yields log lines from `/v1/agent/monitor`. The response is a
+// stream of JSON encoded frames whose data holds the log text.
+//
+pub struct LogStream {
+    response: Response,
+    assembler: Assembler,
+    partial: String,
+    lines: VecDeque<String>,
+    // A corrupt frame ends the stream once the lines before it are read
+    failed: Option<Error>,
+}
+
+impl LogStream {
+    fn new(response: Response) -> Self {
+        LogStream {
+            response,
+            assembler: Assembler::new(),
+            partial: String::new(),
+            lines: VecDeque::new(),
+            failed: None,
+        }
+    }
+
+    //
+    // The next log line, `None` once the agent closes the stream
+    //
+    pub async fn next(&mut self) -> Result<Option<LogEntry>> {
+        loop {
+            if let Some(line) = self.lines.pop_front() {
+                return Ok(Some(LogEntry::parse(&line)));
+            }
+            if let Some(e) = self.failed.take() {
+                return Err(e);
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => {
+                    if let Err(e) = self.add_chunk(&chunk) {
+                        self.failed = Some(e);
+                    }
+                }
+                None => {
+                    if self.partial.is_empty() {
+                        return Ok(None);
+                    }
+                    let line = std::mem::take(&mut self.partial);
+                    return Ok(Some(LogEntry::parse(&line)));
+                }
+            }
+        }
+    }
+
+    fn add_chunk(&mut self, chunk: &Bytes) -> Result<()> {
+        // Frames are JSON with base64 encoded data so anything which is not
+        // utf-8 can only be corrupt and is replaced rather than ending the
+        // stream. Frames are newline terminated but a chunk may hold several
+        // frames or only part of one.
+        let text = String::from_utf8_lossy(chunk);
+        for piece in text.split_inclusive('\n') {
+            let piece = piece.trim_end_matches('\n');
+            if piece.trim().is_empty() {
+                continue;
+            }
+            if let Some(frame) = self.assembler.add::<StreamFrame>(piece)? {
+                self.add_frame(frame);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_frame(&mut self, frame: StreamFrame) {
+        if frame.is_heartbeat() {
+            return;
+        }
+        self.partial.push_str(&String::from_utf8_lossy(&frame.data));
+        while let Some(newline) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=newline).collect();
+            let line = line.trim_end();
+            if !line.is_empty() {
+                self.lines.push_back(line.to_string());
+            }
+        }
+    }
+}
+
+pub struct Agent<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Agent<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Agent { client }
+    }
+
+    // Configuration, membership and stats of the agent the client talks to
+    pub async fn info(&self) -> Result<AgentSelf> {
+        let (agent, _) = self
+            .client
+            .query("/v1/agent/self", &QueryOptions::default())
+            .await?;
+        Ok(agent)
+    }
+
+    // The servers in the gossip pool of the agent's region
+    pub async fn members(&self, options: &QueryOptions) -> Result<(ServerMembers, QueryMeta)> {
+        self.client.query("/v1/agent/members", options).await
+    }
+
+    // Telemetry of the agent in the go-metrics JSON format
+    pub async fn metrics(&self) -> Result<serde_json::Value> {
+        let (metrics, _) = self
+            .client
+            .query("/v1/metrics", &QueryOptions::default())
+            .await?;
+        Ok(metrics)
+    }
+
+    // The servers a client agent is using for RPC
+    pub async fn servers(&self) -> Result<Vec<String>> {
+        let (servers, _) = self
+            .client
+            .query("/v1/agent/servers", &QueryOptions::default())
+            .await?;
+        Ok(servers)
+    }
+
+    // Replace the servers a client agent is using for RPC
+    pub async fn set_servers(&self, addresses: &[&str]) -> Result<()> {
+        let params: Vec<(&str, String)> = addresses
+            .iter()
+            .map(|address| ("address", address.to_string()))
+            .collect();
+        self.client
+            .write::<(), _>(Method::PUT, "/v1/agent/servers", &params, None)
+            .await
+    }
+
+    //
+    // Health of the client and/or server running in the agent. An unhealthy
+    // agent responds with a 500 status and the same body, which is returned
+    // rather than treated as an error.
+    //
+    pub async fn health(&self) -> Result<AgentHealthResponse> {
+        let builder = self.client.request(Method::GET, "/v1/agent/health", &[]);
+        let response = builder.send().await?;
+        let status = response.status();
+        let bytes = response.bytes().await?;
+        if status.is_success() || status == StatusCode::INTERNAL_SERVER_ERROR {
+            if let Ok(health) = serde_json::from_slice(&bytes) {
+                return Ok(health);
+            }
+        }
+        if !status.is_success() {
+            return Err(Error::Api {
+                status: status.as_u16(),
+                message: String::from_utf8_lossy(&bytes).trim().to_string(),
+            });
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    //
+    // Stream the logs of an agent; lines are parsed from either log format
+    //
+    pub async fn monitor(&self, options: &LogMonitorOptions) -> Result<LogStream> {
+        let builder = self
+            .client
+            .request(Method::GET, "/v1/agent/monitor", &options.to_params());
+        let response = self.client.send(builder).await?;
+        Ok(LogStream::new(response))
+    }
+
+    // Capture a runtime profile returning the raw pprof (or trace) bytes
+    pub async fn profile(&self, profile: Profile, options: &ProfileOptions) -> Result<Bytes> {
+        let (bytes, _) = self
+            .client
+            .query_bytes(profile.path(), &options.to_params())
+            .await?;
+        Ok(bytes)
+    }
+
+    // Ask a server agent to join the gossip pool of the given addresses
+    pub async fn join(&self, addresses: &[&str]) -> Result<u32> {
+        let params: Vec<(&str, String)> = addresses
+            .iter()
+            .map(|address| ("address", address.to_string()))
+            .collect();
+        let response: JoinResponse = self
+            .client
+            .write::<(), _>(Method::PUT, "/v1/agent/join", &params, None)
+            .await?;
+        if !response.error.is_empty() {
+            return Err(Error::UnexpectedResponse(format!(
+                "joined {} of {} addresses: {}",
+                response.num_joined,
+                addresses.len(),
+                response.error
+            )));
+        }
+        Ok(response.num_joined)
+    }
+
+    //
+    // Force a failed member into the left state, `prune` removes it from the
+    // member list entirely
+    //
+    pub async fn force_leave(&self, node: &str, prune: bool) -> Result<()> {
+        let mut params = vec![("node", node.to_string())];
+        if prune {
+            params.push(("prune", "true".to_string()));
+        }
+        self.client
+            .write::<(), _>(Method::PUT, "/v1/agent/force-leave", &params, None)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn unhealthy_agent() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/agent/health");
+            StubResponse::raw(
+                500,
+                br#"{"server": {"ok": false, "message": "No cluster leader"}}"#,
+            )
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let health = client.agent().health().await.expect("health");
+        assert!(!health.is_healthy());
+        assert_eq!(health.server.expect("server").message, "No cluster leader");
+    }
+
+    #[tokio::test]
+    async fn monitor_logs() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/agent/monitor");
+            assert_eq!(request.query["log_level"], "debug");
+            assert_eq!(request.query["server_id"], "leader");
+            let first = base64::encode("2021-03-01T10:11:12.123Z [INFO]  agent: first\n2021");
+            let second = base64::encode("-03-01T10:11:13.000Z [DEBUG] http: second\n");
+            let body = format!(
+                "{{\"Data\":\"{}\"}}\n{{}}\n{{\"Data\":\"{}\"}}\n",
+                first, second
+            );
+            StubResponse::raw(200, body.as_bytes())
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let options = LogMonitorOptions {
+            log_level: "debug".to_string(),
+            server_id: Some("leader".to_string()),
+            ..Default::default()
+        };
+        let mut logs = client.agent().monitor(&options).await.expect("monitor");
+        let first = logs.next().await.expect("first").expect("line");
+        assert_eq!(first.level, "INFO");
+        assert_eq!(first.message, "first");
+        let second = logs.next().await.expect("second").expect("line");
+        assert_eq!(second.module, "http");
+        assert_eq!(second.timestamp, "2021-03-01T10:11:13.000Z");
+        assert!(logs.next().await.expect("end").is_none());
+    }
+
+    #[tokio::test]
+    async fn monitor_logs_corrupt_frame() {
+        let address = serve(|_| {
+            let data = base64::encode("2021-03-01T10:11:12.123Z [INFO]  agent: first\n");
+            let body = format!("{{\"Data\":\"{}\"}}\n{{\"Data\":]\n", data);
+            StubResponse::raw(200, body.as_bytes())
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let options = LogMonitorOptions::default();
+        let mut logs = client.agent().monitor(&options).await.expect("monitor");
+        let first = logs.next().await.expect("first").expect("line");
+        assert_eq!(first.message, "first");
+        assert!(matches!(logs.next().await, Err(Error::Json(_))));
+    }
+
+    #[tokio::test]
+    async fn cpu_profile() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/agent/pprof/profile");
+            assert_eq!(request.query["seconds"], "5");
+            assert_eq!(request.query["node_id"], "abc");
+            StubResponse::raw(200, &[0x1f, 0x8b, 0x08])
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let options = ProfileOptions {
+            node_id: Some("abc".to_string()),
+            seconds: Some(5),
+            ..Default::default()
+        };
+        let bytes = client
+            .agent()
+            .profile(Profile::Cpu, &options)
+            .await
+            .expect("profile");
+        assert_eq!(&bytes[..], &[0x1f, 0x8b, 0x08]);
+    }
+
+    #[tokio::test]
+    async fn join_and_force_leave() {
+        let address = serve(|request| match request.path.as_str() {
+            "/v1/agent/join" => {
+                assert_eq!(request.method, "PUT");
+                match request.query["address"].as_str() {
+                    "10.0.0.2" => StubResponse::json(&json!({"num_joined": 1, "error": ""})),
+                    _ => StubResponse::json(&json!({"num_joined": 0, "error": "i/o timeout"})),
+                }
+            }
+            "/v1/agent/force-leave" => {
+                assert_eq!(request.query["node"], "old.global");
+                assert_eq!(request.query["prune"], "true");
+                StubResponse::raw(200, b"")
+            }
+            _ => StubResponse::raw(404, b"not found"),
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        assert_eq!(client.agent().join(&["10.0.0.2"]).await.expect("join"), 1);
+        match client.agent().join(&["10.0.0.9"]).await {
+            Err(Error::UnexpectedResponse(reason)) => {
+                assert_eq!(reason, "joined 0 of 1 addresses: i/o timeout")
+            }
+            other => panic!("expected a join error, got {:?}", other),
+        }
+        client
+            .agent()
+            .force_leave("old.global", true)
+            .await
+            .expect("force leave");
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions};
+use crate::error::Result;
+use crate::model::allocations::{Allocation, AllocationListStub};
+use crate::model::services::AllocCheckStatus;
+
+pub struct Allocations<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Allocations<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Allocations { client }
+    }
+
+    pub async fn list(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(Vec<AllocationListStub>, QueryMeta)> {
+        self.client.query("/v1/allocations", options).await
+    }
+
+    pub async fn info(&self, id: &str, options: &QueryOptions) -> Result<(Allocation, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/allocation/{}", segment(id)), options)
+            .await
+    }
+
+    //
+    // The latest results of the checks of the allocation's services keyed by
+    // check ID. Only services using the built-in provider are checked by
+    // Nomad, the request is answered by the allocation's client.
+    //
+    pub async fn checks(
+        &self,
+        id: &str,
+        options: &QueryOptions,
+    ) -> Result<(HashMap<String, AllocCheckStatus>, QueryMeta)> {
+        self.client
+            .query(
+                &format!("/v1/client/allocation/{}/checks", segment(id)),
+                options,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use crate::model::namespaces::ALL_NAMESPACES;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn list_across_namespaces() {
+        let address = serve(|request| {
+            let namespace = request.query.get("namespace").cloned().unwrap_or_default();
+            StubResponse::json(&json!([
+                {"ID": "a1", "Namespace": "default", "JobID": "web", "ClientStatus": "running"},
+                {"ID": "a2", "Namespace": namespace, "JobID": "api", "ClientStatus": "running"}
+            ]))
+        })
+        .await;
+
+        // A namespace in the query options wins over the client's namespace
+        let client = NomadClient::new(&address).with_namespace("api-prod");
+        let options = QueryOptions {
+            namespace: Some(ALL_NAMESPACES.to_string()),
+            ..Default::default()
+        };
+        let (allocs, _) = client.allocations().list(&options).await.expect("list");
+        assert_eq!(allocs[1].namespace, "*");
+
+        let (allocs, _) = client
+            .allocations()
+            .list(&QueryOptions::default())
+            .await
+            .expect("list");
+        assert_eq!(allocs[1].namespace, "api-prod");
+    }
+}
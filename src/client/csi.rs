@@ -0,0 +1,319 @@
+use reqwest::Method;
+
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions, WriteMeta};
+use crate::error::{Error, Result};
+use crate::model::csi::{
+    CSIPlugin, CSIPluginListStub, CSISecrets, CSISnapshot, CSISnapshotListResponse,
+    CSISnapshotRequest, CSIVolume, CSIVolumeListStub, CSIVolumeRequest,
+};
+
+// CSIVolumeFilter narrows a volume listing to a plugin and/or node
+#[derive(Debug, Default, Clone)]
+pub struct CSIVolumeFilter {
+    pub plugin_id: Option<String>,
+    pub node_id: Option<String>,
+}
+
+impl CSIVolumeFilter {
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("type", "csi".to_string())];
+        if let Some(ref plugin_id) = self.plugin_id {
+            params.push(("plugin_id", plugin_id.clone()));
+        }
+        if let Some(ref node_id) = self.node_id {
+            params.push(("node_id", node_id.clone()));
+        }
+        params
+    }
+}
+
+pub struct CSIVolumes<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> CSIVolumes<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        CSIVolumes { client }
+    }
+
+    pub async fn list(
+        &self,
+        filter: &CSIVolumeFilter,
+        options: &QueryOptions,
+    ) -> Result<(Vec<CSIVolumeListStub>, QueryMeta)> {
+        self.client
+            .query_with("/v1/volumes", &filter.to_params(), options)
+            .await
+    }
+
+    pub async fn info(&self, id: &str, options: &QueryOptions) -> Result<(CSIVolume, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/volume/csi/{}", segment(id)), options)
+            .await
+    }
+
+    //
+    // Register an existing volume, identified by its `external_id`, with
+    // Nomad
+    //
+    pub async fn register(&self, volume: &CSIVolume) -> Result<WriteMeta> {
+        if volume.id.is_empty() {
+            return Err(Error::InvalidRequest("missing volume ID".to_string()));
+        }
+        let request = CSIVolumeRequest {
+            volumes: vec![volume.clone()],
+        };
+        let ((), meta) = self
+            .client
+            .write_with_meta(
+                Method::PUT,
+                &format!("/v1/volume/csi/{}", segment(&volume.id)),
+                &[],
+                Some(&request),
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    //
+    // Remove a volume from Nomad without deleting it from the storage
+    // provider. Volumes with claims can only be deregistered with `force`.
+    //
+    pub async fn deregister(&self, id: &str, force: bool) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/volume/csi/{}", segment(id)),
+                &[("force", force.to_string())],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    //
+    // Create a volume through its plugin and register it, returning the
+    // volume with the fields populated by the storage provider
+    //
+    pub async fn create(&self, volume: &CSIVolume) -> Result<(Vec<CSIVolume>, WriteMeta)> {
+        if volume.id.is_empty() {
+            return Err(Error::InvalidRequest("missing volume ID".to_string()));
+        }
+        let request = CSIVolumeRequest {
+            volumes: vec![volume.clone()],
+        };
+        let (response, meta): (CSIVolumeRequest, _) = self
+            .client
+            .write_with_meta(
+                Method::PUT,
+                &format!("/v1/volume/csi/{}/create", segment(&volume.id)),
+                &[],
+                Some(&request),
+            )
+            .await?;
+        Ok((response.volumes, meta))
+    }
+
+    // Deregister a volume and delete it from the storage provider
+    pub async fn delete(&self, id: &str, secrets: &CSISecrets) -> Result<WriteMeta> {
+        self.delete_with_secrets(
+            &format!("/v1/volume/csi/{}/delete", segment(id)),
+            &[],
+            secrets,
+        )
+        .await
+    }
+
+    // Release the claims held on a volume by the given node
+    pub async fn detach(&self, id: &str, node_id: &str) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/volume/csi/{}/detach", segment(id)),
+                &[("node", node_id.to_string())],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    //
+    // Snapshots
+    //
+
+    pub async fn create_snapshot(
+        &self,
+        snapshot: &CSISnapshot,
+    ) -> Result<(Vec<CSISnapshot>, WriteMeta)> {
+        if snapshot.source_volume_id.is_empty() {
+            return Err(Error::InvalidRequest(
+                "missing source volume ID".to_string(),
+            ));
+        }
+        let request = CSISnapshotRequest {
+            snapshots: vec![snapshot.clone()],
+        };
+        let (response, meta): (CSISnapshotRequest, _) = self
+            .client
+            .write_with_meta(Method::POST, "/v1/volumes/snapshot", &[], Some(&request))
+            .await?;
+        Ok((response.snapshots, meta))
+    }
+
+    pub async fn delete_snapshot(&self, snapshot: &CSISnapshot) -> Result<WriteMeta> {
+        let params = [
+            ("plugin_id", snapshot.plugin_id.clone()),
+            ("snapshot_id", snapshot.id.clone()),
+        ];
+        self.delete_with_secrets("/v1/volumes/snapshot", &params, &snapshot.secrets)
+            .await
+    }
+
+    //
+    // List the snapshots known to a plugin, `CSISnapshotListResponse::next_token`
+    // is set when the plugin has further pages
+    //
+    pub async fn list_snapshots(
+        &self,
+        plugin_id: &str,
+        options: &QueryOptions,
+    ) -> Result<(CSISnapshotListResponse, QueryMeta)> {
+        self.client
+            .query_with(
+                "/v1/volumes/snapshot",
+                &[("plugin_id", plugin_id.to_string())],
+                options,
+            )
+            .await
+    }
+
+    //
+    // Secrets for deletes are passed in the `X-Nomad-CSI-Secrets` header as
+    // comma separated `key=value` pairs
+    //
+    async fn delete_with_secrets(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+        secrets: &CSISecrets,
+    ) -> Result<WriteMeta> {
+        let mut builder = self.client.request(Method::DELETE, path, params);
+        if !secrets.is_empty() {
+            let mut pairs: Vec<String> = secrets
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value.expose()))
+                .collect();
+            pairs.sort();
+            builder = builder.header("X-Nomad-CSI-Secrets", pairs.join(","));
+        }
+        let response = self.client.send(builder).await?;
+        Ok(WriteMeta {
+            last_index: QueryMeta::from_headers(response.headers()).last_index,
+        })
+    }
+}
+
+pub struct CSIPlugins<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> CSIPlugins<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        CSIPlugins { client }
+    }
+
+    pub async fn list(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(Vec<CSIPluginListStub>, QueryMeta)> {
+        self.client
+            .query_with("/v1/plugins", &[("type", "csi".to_string())], options)
+            .await
+    }
+
+    pub async fn info(&self, id: &str, options: &QueryOptions) -> Result<(CSIPlugin, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/plugin/csi/{}", segment(id)), options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn create_and_delete_volume() {
+        let address = serve(
+            |request| match (request.method.as_str(), request.path.as_str()) {
+                ("PUT", "/v1/volume/csi/mysql/create") => {
+                    let body: serde_json::Value =
+                        serde_json::from_slice(&request.body).expect("volume body");
+                    assert_eq!(body["Volumes"][0]["PluginID"], json!("aws-ebs0"));
+                    assert_eq!(body["Volumes"][0]["Secrets"]["password"], json!("hunter2"));
+                    StubResponse::json(&json!({
+                        "Volumes": [{
+                            "ID": "mysql",
+                            "ExternalID": "vol-0123456789abcdef",
+                            "PluginID": "aws-ebs0",
+                            "Capacity": 10737418240u64
+                        }]
+                    }))
+                    .index(21)
+                }
+                ("DELETE", "/v1/volume/csi/mysql/delete") => {
+                    assert_eq!(
+                        request
+                            .headers
+                            .get("x-nomad-csi-secrets")
+                            .map(String::as_str),
+                        Some("password=hunter2")
+                    );
+                    StubResponse::raw(200, b"").index(22)
+                }
+                ("DELETE", "/v1/volume/csi/mysql/detach") => {
+                    assert_eq!(
+                        request.query.get("node").map(String::as_str),
+                        Some("f7f7a2c9")
+                    );
+                    StubResponse::raw(200, b"").index(23)
+                }
+                _ => StubResponse::raw(404, b"not found"),
+            },
+        )
+        .await;
+
+        let client = NomadClient::new(&address);
+        let mut volume = CSIVolume {
+            id: "mysql".to_string(),
+            name: "mysql".to_string(),
+            plugin_id: "aws-ebs0".to_string(),
+            ..Default::default()
+        };
+        volume
+            .secrets
+            .insert("password".to_string(), "hunter2".into());
+
+        let (created, meta) = client.csi_volumes().create(&volume).await.expect("create");
+        assert_eq!(meta.last_index, 21);
+        assert_eq!(created[0].external_id, "vol-0123456789abcdef");
+
+        let meta = client
+            .csi_volumes()
+            .delete("mysql", &volume.secrets)
+            .await
+            .expect("delete");
+        assert_eq!(meta.last_index, 22);
+
+        let meta = client
+            .csi_volumes()
+            .detach("mysql", "f7f7a2c9")
+            .await
+            .expect("detach");
+        assert_eq!(meta.last_index, 23);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::client::monitor::{DeploymentMonitor, MonitorOptions};
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions};
+use crate::error::Result;
+use crate::model::allocations::AllocationListStub;
+use crate::model::deployments::Deployment;
+
+pub struct Deployments<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Deployments<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Deployments { client }
+    }
+
+    pub async fn list(&self, options: &QueryOptions) -> Result<(Vec<Deployment>, QueryMeta)> {
+        self.client.query("/v1/deployments", options).await
+    }
+
+    pub async fn info(&self, id: &str, options: &QueryOptions) -> Result<(Deployment, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/deployment/{}", segment(id)), options)
+            .await
+    }
+
+    pub async fn allocations(
+        &self,
+        id: &str,
+        options: &QueryOptions,
+    ) -> Result<(Vec<AllocationListStub>, QueryMeta)> {
+        self.client
+            .query(
+                &format!("/v1/deployment/allocations/{}", segment(id)),
+                options,
+            )
+            .await
+    }
+
+    //
+    // Monitor a deployment until it succeeds, fails or is cancelled
+    //
+    pub fn monitor(&self, id: &str, options: MonitorOptions) -> DeploymentMonitor<'a> {
+        DeploymentMonitor::new(self.client, id, options)
+    }
+}
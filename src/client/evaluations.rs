@@ -0,0 +1,195 @@
+use crate::client::monitor::{EvalMonitor, MonitorOptions};
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions};
+use crate::error::Result;
+use crate::model::allocations::AllocationListStub;
+use crate::model::evaluations::Evaluation;
+
+// EvaluationFilter narrows an evaluation listing to a job and/or status
+#[derive(Debug, Default, Clone)]
+pub struct EvaluationFilter {
+    pub job: Option<String>,
+    pub status: Option<String>,
+}
+
+impl EvaluationFilter {
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(ref job) = self.job {
+            params.push(("job", job.clone()));
+        }
+        if let Some(ref status) = self.status {
+            params.push(("status", status.clone()));
+        }
+        params
+    }
+}
+
+pub struct Evaluations<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Evaluations<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Evaluations { client }
+    }
+
+    //
+    // List a single page of evaluations, `QueryMeta::next_token` is set when
+    // further pages are available
+    //
+    pub async fn list(
+        &self,
+        filter: &EvaluationFilter,
+        options: &QueryOptions,
+    ) -> Result<(Vec<Evaluation>, QueryMeta)> {
+        self.client
+            .query_with("/v1/evaluations", &filter.to_params(), options)
+            .await
+    }
+
+    //
+    // List evaluations, following pagination tokens until all pages are read
+    //
+    pub async fn list_all(
+        &self,
+        filter: &EvaluationFilter,
+        options: &QueryOptions,
+    ) -> Result<(Vec<Evaluation>, QueryMeta)> {
+        let mut options = options.clone();
+        let mut evaluations = Vec::new();
+        loop {
+            let (mut page, meta) = self.list(filter, &options).await?;
+            evaluations.append(&mut page);
+            match meta.next_token {
+                Some(ref token) => options.next_token = Some(token.clone()),
+                None => return Ok((evaluations, meta)),
+            }
+        }
+    }
+
+    pub async fn info(&self, id: &str, options: &QueryOptions) -> Result<(Evaluation, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/evaluation/{}", segment(id)), options)
+            .await
+    }
+
+    pub async fn allocations(
+        &self,
+        id: &str,
+        options: &QueryOptions,
+    ) -> Result<(Vec<AllocationListStub>, QueryMeta)> {
+        self.client
+            .query(
+                &format!("/v1/evaluation/{}/allocations", segment(id)),
+                options,
+            )
+            .await
+    }
+
+    //
+    // Monitor an evaluation until it (and any follow up evaluations) reach a
+    // terminal status
+    //
+    pub fn monitor(&self, id: &str, options: MonitorOptions) -> EvalMonitor<'a> {
+        EvalMonitor::new(self.client, id, options)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    pub(crate) fn eval_json(id: &str, status: &str) -> serde_json::Value {
+        json!({
+            "ID": id,
+            "Namespace": "default",
+            "Priority": 50,
+            "Type": "service",
+            "TriggeredBy": "job-register",
+            "JobID": "example",
+            "JobModifyIndex": 30,
+            "NodeID": "",
+            "NodeModifyIndex": 0,
+            "DeploymentID": "",
+            "Status": status,
+            "StatusDescription": "",
+            "Wait": 0,
+            "WaitUntil": "0001-01-01T00:00:00Z",
+            "NextEval": "",
+            "PreviousEval": "",
+            "BlockedEval": "",
+            "FailedTGAllocs": null,
+            "ClassEligibility": null,
+            "EscapedComputedClass": false,
+            "QueuedAllocations": null,
+            "SnapshotIndex": 33,
+            "CreateIndex": 33,
+            "ModifyIndex": 35,
+            "CreateTime": 1613538639,
+            "ModifyTime": 1613538639
+        })
+    }
+
+    #[tokio::test]
+    async fn list_filters_and_pages() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/evaluations");
+            assert_eq!(
+                request.query.get("job").map(String::as_str),
+                Some("example")
+            );
+            assert_eq!(
+                request.query.get("status").map(String::as_str),
+                Some("complete")
+            );
+            match request.query.get("next_token") {
+                None => StubResponse::json(&json!([eval_json("a", "complete")]))
+                    .index(35)
+                    .header("X-Nomad-NextToken", "b"),
+                Some(_) => StubResponse::json(&json!([eval_json("b", "complete")])).index(36),
+            }
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let filter = EvaluationFilter {
+            job: Some("example".to_string()),
+            status: Some("complete".to_string()),
+        };
+        let (evals, meta) = client
+            .evaluations()
+            .list_all(&filter, &QueryOptions::default())
+            .await
+            .expect("list evaluations");
+        assert_eq!(evals.len(), 2);
+        assert_eq!(evals[1].id, "b");
+        assert_eq!(meta.last_index, 36);
+    }
+
+    #[tokio::test]
+    async fn info_not_found() {
+        let address = serve(|request| {
+            assert_eq!(request.method, "GET");
+            assert_eq!(
+                request.headers.get("x-nomad-token").map(String::as_str),
+                Some("s3cr3t")
+            );
+            StubResponse::raw(404, b"eval not found")
+        })
+        .await;
+        let client = NomadClient::new(&address).with_token("s3cr3t");
+        let result = client
+            .evaluations()
+            .info("missing", &QueryOptions::default())
+            .await;
+        match result {
+            Err(crate::error::Error::Api { status, message }) => {
+                assert_eq!(status, 404);
+                assert_eq!(message, "eval not found");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
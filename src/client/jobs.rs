@@ -0,0 +1,571 @@
+use reqwest::Method;
+
+use std::collections::HashMap;
+
+use crate::client::monitor::{EvalReport, MonitorOptions};
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions};
+use crate::error::{Error, Result};
+use crate::model::deployments::{Deployment, DEPLOYMENT_STATUS_SUCCESSFUL};
+use crate::model::jobs::{
+    Job, JobDispatchRequest, JobDispatchResponse, JobListStub, JobRegisterRequest,
+    JobRegisterResponse, JobRevertRequest, JobStabilityRequest, JobStabilityResponse, JobType,
+    JobsParseRequest, PeriodicForceResponse,
+};
+use crate::model::scaling::{JobScaleStatusResponse, ScalingRequest};
+
+#[derive(Debug, Default, Clone)]
+pub struct RunOptions {
+    // Only register if the job's modify index matches, 0 requires a new job
+    pub check_index: Option<u64>,
+    pub policy_override: bool,
+    pub preserve_counts: bool,
+    // Return immediately after registering instead of monitoring
+    pub detach: bool,
+    pub monitor: MonitorOptions,
+}
+
+// RunReport describes each phase of a `Jobs::run`
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub register: JobRegisterResponse,
+    // Not present when detached or when registering created no evaluation,
+    // as is the case for periodic and parameterized jobs
+    pub evaluation: Option<EvalReport>,
+    // Only present for service jobs whose evaluation created a deployment
+    pub deployment: Option<Deployment>,
+}
+
+impl RunReport {
+    pub fn is_success(&self) -> bool {
+        let evaluated = self
+            .evaluation
+            .as_ref()
+            .is_none_or(|report| report.is_success());
+        let deployed = self
+            .deployment
+            .as_ref()
+            .is_none_or(|d| d.status == DEPLOYMENT_STATUS_SUCCESSFUL);
+        evaluated && deployed
+    }
+}
+
+pub struct Jobs<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Jobs<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Jobs { client }
+    }
+
+    pub async fn list(&self, options: &QueryOptions) -> Result<(Vec<JobListStub>, QueryMeta)> {
+        self.client.query("/v1/jobs", options).await
+    }
+
+    pub async fn info(&self, id: &str, options: &QueryOptions) -> Result<(Job, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/job/{}", segment(id)), options)
+            .await
+    }
+
+    //
+    // Convert an HCL2 job specification into a job with the agent's parser.
+    // `variables` supply values for the job's `variable` blocks and
+    // `canonicalize` fills in defaults as registering would.
+    //
+    pub async fn parse_hcl(
+        &self,
+        text: &str,
+        canonicalize: bool,
+        variables: &HashMap<String, String>,
+    ) -> Result<Job> {
+        let mut names: Vec<&String> = variables.keys().collect();
+        names.sort();
+        let mut body = hcl::Body::builder();
+        for name in names {
+            let ident = hcl::Identifier::new(name.as_str()).map_err(|_| {
+                Error::InvalidRequest(format!("invalid variable name \"{}\"", name))
+            })?;
+            body = body.add_attribute((ident, variables[name].as_str()));
+        }
+        let request = JobsParseRequest {
+            job_hcl: text.to_string(),
+            hcl_v1: false,
+            variables: hcl::to_string(&body.build())?,
+            canonicalize,
+        };
+        self.client
+            .write(Method::POST, "/v1/jobs/parse", &[], Some(&request))
+            .await
+    }
+
+    pub async fn register(&self, request: &JobRegisterRequest) -> Result<JobRegisterResponse> {
+        self.client
+            .write(Method::PUT, "/v1/jobs", &[], Some(request))
+            .await
+    }
+
+    //
+    // Revert a job to an earlier version. If `enforce_prior_version` is given
+    // the revert only happens if the current version matches it.
+    //
+    pub async fn revert(
+        &self,
+        id: &str,
+        version: u64,
+        enforce_prior_version: Option<u64>,
+    ) -> Result<JobRegisterResponse> {
+        let request = JobRevertRequest {
+            job_id: id.to_string(),
+            job_version: version,
+            enforce_prior_version,
+        };
+        self.client
+            .write(
+                Method::PUT,
+                &format!("/v1/job/{}/revert", segment(id)),
+                &[],
+                Some(&request),
+            )
+            .await
+    }
+
+    pub async fn stable(
+        &self,
+        id: &str,
+        version: u64,
+        stable: bool,
+    ) -> Result<JobStabilityResponse> {
+        let request = JobStabilityRequest {
+            job_id: id.to_string(),
+            job_version: version,
+            stable,
+        };
+        self.client
+            .write(
+                Method::PUT,
+                &format!("/v1/job/{}/stable", segment(id)),
+                &[],
+                Some(&request),
+            )
+            .await
+    }
+
+    //
+    // Dispatch an instance of a parameterized job. The payload and meta are
+    // checked against the job's parameterized config before dispatching.
+    //
+    pub async fn dispatch(
+        &self,
+        id: &str,
+        payload: Vec<u8>,
+        meta: HashMap<String, String>,
+    ) -> Result<JobDispatchResponse> {
+        let (job, _) = self.info(id, &QueryOptions::default()).await?;
+        let config = job
+            .parameterized_job
+            .ok_or_else(|| Error::InvalidRequest(format!("job \"{}\" is not parameterized", id)))?;
+        config.check_dispatch(&payload, &meta).map_err(|reason| {
+            Error::InvalidRequest(format!("dispatch of \"{}\": {}", id, reason))
+        })?;
+
+        let request = JobDispatchRequest {
+            job_id: id.to_string(),
+            payload,
+            meta,
+        };
+        self.client
+            .write(
+                Method::PUT,
+                &format!("/v1/job/{}/dispatch", segment(id)),
+                &[],
+                Some(&request),
+            )
+            .await
+    }
+
+    // Force a new instance of a periodic job to be launched immediately
+    pub async fn periodic_force(&self, id: &str) -> Result<PeriodicForceResponse> {
+        self.client
+            .write::<(), _>(
+                Method::PUT,
+                &format!("/v1/job/{}/periodic/force", segment(id)),
+                &[],
+                None,
+            )
+            .await
+    }
+
+    pub async fn scale_status(
+        &self,
+        id: &str,
+        options: &QueryOptions,
+    ) -> Result<(JobScaleStatusResponse, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/job/{}/scale", segment(id)), options)
+            .await
+    }
+
+    //
+    // Scale the task group named by the request target. The request's job is
+    // filled in from `id` if not already set.
+    //
+    pub async fn scale(&self, id: &str, request: &ScalingRequest) -> Result<JobRegisterResponse> {
+        if request.target.group.is_none() {
+            return Err(Error::InvalidRequest(
+                "scaling request must target a group".to_string(),
+            ));
+        }
+        let mut request = request.clone();
+        request.target.job.get_or_insert_with(|| id.to_string());
+        self.client
+            .write(
+                Method::POST,
+                &format!("/v1/job/{}/scale", segment(id)),
+                &[],
+                Some(&request),
+            )
+            .await
+    }
+
+    //
+    // Register a job and follow it through evaluation and, for service jobs,
+    // deployment in the same way `nomad job run` does
+    //
+    pub async fn run(&self, job: Job, options: RunOptions) -> Result<RunReport> {
+        let skip_deployment = matches!(job.job_type, Some(JobType::Batch) | Some(JobType::System));
+        let request = JobRegisterRequest {
+            job,
+            enforce_index: options.check_index.is_some(),
+            job_modify_index: options.check_index.unwrap_or_default(),
+            policy_override: options.policy_override,
+            preserve_counts: options.preserve_counts,
+        };
+        let register = self.register(&request).await?;
+
+        let mut report = RunReport {
+            register,
+            evaluation: None,
+            deployment: None,
+        };
+        if options.detach || report.register.eval_id.is_empty() {
+            return Ok(report);
+        }
+
+        let evaluation = self
+            .client
+            .evaluations()
+            .monitor(&report.register.eval_id, options.monitor.clone())
+            .run()
+            .await?;
+        let deployment_id = evaluation.deployment_id().map(String::from);
+        report.evaluation = Some(evaluation);
+
+        if let Some(id) = deployment_id.filter(|_| !skip_deployment) {
+            let deployment = self
+                .client
+                .deployments()
+                .monitor(&id, options.monitor)
+                .run()
+                .await?;
+            report.deployment = Some(deployment);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::evaluations::tests::eval_json;
+    use crate::client::stub_server::{serve, StubResponse};
+    use crate::model::jobs::JobSpec;
+    use crate::model::tasks::{RestartPolicy, Task, TaskGroup};
+    use serde_json::json;
+
+    use std::time::Duration;
+
+    fn deployment_json(status: &str) -> serde_json::Value {
+        json!({
+            "ID": "d1",
+            "Namespace": "default",
+            "JobID": "example",
+            "JobVersion": 0,
+            "JobModifyIndex": 30,
+            "JobSpecModifyIndex": 30,
+            "JobCreateIndex": 30,
+            "IsMultiregion": false,
+            "TaskGroups": null,
+            "Status": status,
+            "StatusDescription": "",
+            "CreateIndex": 36,
+            "ModifyIndex": 40
+        })
+    }
+
+    fn options(detach: bool) -> RunOptions {
+        RunOptions {
+            check_index: Some(0),
+            detach,
+            monitor: MonitorOptions {
+                wait_time: Duration::from_millis(10),
+                poll_interval: Duration::from_millis(1),
+                timeout: Some(Duration::from_secs(5)),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn job(job_type: JobType) -> Job {
+        Job {
+            id: Some("example".to_string()),
+            job_type: Some(job_type),
+            ..Default::default()
+        }
+    }
+
+    async fn stub() -> NomadClient {
+        let address = serve(|request| match request.path.as_str() {
+            "/v1/jobs" => {
+                assert_eq!(request.method, "PUT");
+                let body: serde_json::Value =
+                    serde_json::from_slice(&request.body).expect("register body");
+                assert_eq!(body["EnforceIndex"], json!(true));
+                assert_eq!(body["Job"]["ID"], json!("example"));
+                StubResponse::json(&json!({
+                    "EvalID": "e1",
+                    "EvalCreateIndex": 35,
+                    "JobModifyIndex": 35,
+                    "Warnings": ""
+                }))
+            }
+            "/v1/evaluation/e1" => {
+                let mut eval = eval_json("e1", "complete");
+                eval["DeploymentID"] = json!("d1");
+                StubResponse::json(&eval).index(36)
+            }
+            "/v1/deployment/d1" => StubResponse::json(&deployment_json("successful")).index(40),
+            _ => StubResponse::json(&json!([])),
+        })
+        .await;
+        NomadClient::new(&address)
+    }
+
+    #[tokio::test]
+    async fn run_service_job() {
+        let client = stub().await;
+        let report = client
+            .jobs()
+            .run(job(JobType::Service), options(false))
+            .await
+            .expect("run");
+        assert_eq!(report.register.eval_id, "e1");
+        assert!(report.evaluation.is_some());
+        assert_eq!(
+            report.deployment.as_ref().map(|d| d.id.as_str()),
+            Some("d1")
+        );
+        assert!(report.is_success());
+    }
+
+    #[tokio::test]
+    async fn run_batch_job_skips_deployment() {
+        let client = stub().await;
+        let report = client
+            .jobs()
+            .run(job(JobType::Batch), options(false))
+            .await
+            .expect("run");
+        assert!(report.evaluation.is_some());
+        assert!(report.deployment.is_none());
+    }
+
+    #[tokio::test]
+    async fn run_detached() {
+        let client = stub().await;
+        let report = client
+            .jobs()
+            .run(job(JobType::Service), options(true))
+            .await
+            .expect("run");
+        assert!(report.evaluation.is_none());
+        assert!(report.is_success());
+    }
+
+    #[test]
+    fn serialize_register_request() {
+        let spec: JobSpec = serde_json::from_str(r#"{"Job": {"ID": "example"}}"#).expect("spec");
+        let request = JobRegisterRequest {
+            job: spec.job,
+            ..Default::default()
+        };
+        let js = serde_json::to_value(&request).expect("serialize");
+        assert_eq!(js["Job"]["TaskGroups"], json!([]));
+        assert_eq!(js["EnforceIndex"], json!(false));
+    }
+
+    #[tokio::test]
+    async fn register_sends_nanosecond_durations() {
+        let address = serve(|request| {
+            let body: serde_json::Value =
+                serde_json::from_slice(&request.body).expect("register body");
+            let group = &body["Job"]["TaskGroups"][0];
+            assert_eq!(group["RestartPolicy"]["Delay"], json!(15_000_000_000u64));
+            assert_eq!(group["Tasks"][0]["KillTimeout"], json!(5_000_000_000u64));
+            StubResponse::json(&json!({"EvalID": "e1", "JobModifyIndex": 35}))
+        })
+        .await;
+        let job = Job {
+            id: Some("example".to_string()),
+            task_groups: vec![TaskGroup {
+                name: "web".to_string(),
+                restart_policy: Some(RestartPolicy {
+                    delay: Some(Duration::from_secs(15)),
+                    ..Default::default()
+                }),
+                tasks: vec![Task {
+                    name: "server".to_string(),
+                    driver: Some("docker".to_string()),
+                    kill_timeout: Some(Duration::from_secs(5)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let request = JobRegisterRequest {
+            job,
+            ..Default::default()
+        };
+        let response = NomadClient::new(&address)
+            .jobs()
+            .register(&request)
+            .await
+            .expect("register");
+        assert_eq!(response.eval_id, "e1");
+    }
+
+    async fn parameterized_stub() -> NomadClient {
+        let address = serve(|request| match request.path.as_str() {
+            "/v1/job/batch" => StubResponse::json(&json!({
+                "ID": "batch",
+                "Type": "batch",
+                "ParameterizedJob": {
+                    "Payload": "required",
+                    "MetaRequired": ["input"],
+                    "MetaOptional": null
+                }
+            })),
+            "/v1/job/batch/dispatch" => {
+                let body: serde_json::Value =
+                    serde_json::from_slice(&request.body).expect("dispatch body");
+                assert_eq!(body["Payload"], json!("ZGF0YQ=="));
+                assert_eq!(body["Meta"]["input"], json!("s3://bucket/key"));
+                StubResponse::json(&json!({
+                    "Index": 13,
+                    "JobCreateIndex": 12,
+                    "EvalCreateIndex": 13,
+                    "EvalID": "e2",
+                    "DispatchedJobID": "batch/dispatch-1485408778-81644024"
+                }))
+            }
+            "/v1/job/batch/periodic/force" => {
+                assert_eq!(request.method, "PUT");
+                StubResponse::json(&json!({"EvalID": "e3", "EvalCreateIndex": 14, "Index": 14}))
+            }
+            "/v1/job/batch/revert" => {
+                let body: serde_json::Value =
+                    serde_json::from_slice(&request.body).expect("revert body");
+                assert_eq!(body["JobVersion"], json!(1));
+                assert_eq!(body["EnforcePriorVersion"], json!(2));
+                StubResponse::json(&json!({"EvalID": "e4", "JobModifyIndex": 15}))
+            }
+            _ => StubResponse::raw(404, b"job not found"),
+        })
+        .await;
+        NomadClient::new(&address)
+    }
+
+    #[tokio::test]
+    async fn dispatch_parameterized_job() {
+        let client = parameterized_stub().await;
+        let mut meta = HashMap::new();
+        meta.insert("input".to_string(), "s3://bucket/key".to_string());
+        let response = client
+            .jobs()
+            .dispatch("batch", b"data".to_vec(), meta)
+            .await
+            .expect("dispatch");
+        assert_eq!(
+            response.dispatched_job_id,
+            "batch/dispatch-1485408778-81644024"
+        );
+        assert_eq!(response.eval_id, "e2");
+    }
+
+    #[tokio::test]
+    async fn dispatch_rejected_locally() {
+        let client = parameterized_stub().await;
+        let result = client
+            .jobs()
+            .dispatch("batch", Vec::new(), HashMap::new())
+            .await;
+        match result {
+            Err(Error::InvalidRequest(reason)) => {
+                assert!(reason.contains("payload is required"));
+                assert!(reason.contains("missing required meta keys: input"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn scale_group() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/job/example/scale");
+            assert_eq!(request.method, "POST");
+            let body: serde_json::Value =
+                serde_json::from_slice(&request.body).expect("scale body");
+            assert_eq!(body["Count"], json!(3));
+            assert_eq!(body["Target"], json!({"Job": "example", "Group": "cache"}));
+            assert_eq!(body["Meta"]["reason"], json!("load"));
+            StubResponse::json(
+                &json!({"EvalID": "e5", "EvalCreateIndex": 20, "JobModifyIndex": 20}),
+            )
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let mut request = ScalingRequest {
+            count: Some(3),
+            message: "scaled by autoscaler".to_string(),
+            ..Default::default()
+        };
+        request.target.group = Some("cache".to_string());
+        request.meta.insert("reason".to_string(), json!("load"));
+        let response = client
+            .jobs()
+            .scale("example", &request)
+            .await
+            .expect("scale");
+        assert_eq!(response.eval_id, "e5");
+
+        request.target.group = None;
+        let result = client.jobs().scale("example", &request).await;
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn revert_and_force() {
+        let client = parameterized_stub().await;
+        let response = client
+            .jobs()
+            .revert("batch", 1, Some(2))
+            .await
+            .expect("revert");
+        assert_eq!(response.eval_id, "e4");
+        let response = client.jobs().periodic_force("batch").await.expect("force");
+        assert_eq!(response.eval_id, "e3");
+    }
+}
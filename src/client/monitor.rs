@@ -0,0 +1,380 @@
+use tokio::time::{sleep, Instant};
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::client::{NomadClient, QueryOptions};
+use crate::error::{Error, Result};
+use crate::model::allocations::{AllocationListStub, AllocationMetric};
+use crate::model::deployments::Deployment;
+use crate::model::evaluations::{Evaluation, EVAL_STATUS_COMPLETE};
+
+#[derive(Debug, Clone)]
+pub struct MonitorOptions {
+    // Maximum time the agent holds each blocking query open
+    pub wait_time: Duration,
+    // Delay between requests when a response does not advance the index
+    pub poll_interval: Duration,
+    // Give up after this long, `None` waits indefinitely
+    pub timeout: Option<Duration>,
+}
+
+impl Default for MonitorOptions {
+    fn default() -> Self {
+        MonitorOptions {
+            wait_time: Duration::from_secs(30),
+            poll_interval: Duration::from_secs(1),
+            timeout: None,
+        }
+    }
+}
+
+impl MonitorOptions {
+    pub(crate) fn blocking_query(&self, index: u64) -> QueryOptions {
+        QueryOptions {
+            wait_index: Some(index).filter(|i| *i > 0),
+            wait_time: Some(self.wait_time),
+            ..Default::default()
+        }
+    }
+
+    // Pause before the next poll if the last response did not advance the index
+    pub(crate) async fn pace(&self, index: u64, last_index: u64) {
+        if last_index <= index {
+            sleep(self.poll_interval).await;
+        }
+    }
+}
+
+pub(crate) fn expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+// EvalReport is the outcome of following an evaluation chain
+#[derive(Debug, Default, Clone)]
+pub struct EvalReport {
+    // Evaluations in the order they were followed, the last is the final one
+    pub evaluations: Vec<Evaluation>,
+    // Evaluation created to retry placements which could not be made
+    pub blocked_eval: Option<Evaluation>,
+    // Allocations created or updated by the followed evaluations
+    pub allocations: Vec<AllocationListStub>,
+}
+
+impl EvalReport {
+    pub fn evaluation(&self) -> Option<&Evaluation> {
+        self.evaluations.last()
+    }
+
+    //
+    // Placement failures keyed by task group, later evaluations in the chain
+    // take precedence over earlier ones
+    //
+    pub fn placement_failures(&self) -> HashMap<&str, &AllocationMetric> {
+        let mut failures = HashMap::new();
+        for eval in self.evaluations.iter() {
+            for (group, metric) in eval.failed_task_group_allocs.iter() {
+                failures.insert(group.as_str(), metric);
+            }
+        }
+        failures
+    }
+
+    pub fn deployment_id(&self) -> Option<&str> {
+        self.evaluations
+            .iter()
+            .rev()
+            .map(|eval| eval.deployment_id.as_str())
+            .find(|id| !id.is_empty())
+    }
+
+    //
+    // Summarize placement failures per task group in the form printed by
+    // `nomad job run`, empty if every allocation was placed
+    //
+    pub fn explain_failures(&self) -> String {
+        let mut failures: Vec<(&str, &AllocationMetric)> =
+            self.placement_failures().into_iter().collect();
+        if failures.is_empty() {
+            return String::new();
+        }
+        failures.sort_by_key(|(group, _)| *group);
+
+        let mut lines = Vec::new();
+        for (group, metric) in failures {
+            let count = metric.coalesced_failures + 1;
+            lines.push(format!(
+                "Task Group \"{}\" (failed to place {} allocation{}):",
+                group,
+                count,
+                if count == 1 { "" } else { "s" }
+            ));
+            for line in metric.explain().lines() {
+                lines.push(format!("  {}", line));
+            }
+        }
+        if let Some(ref blocked) = self.blocked_eval {
+            lines.push(format!(
+                "Evaluation \"{}\" waiting for additional capacity to place remainder",
+                blocked.id
+            ));
+        }
+        lines.join("\n")
+    }
+
+    // True if the final evaluation completed and placed every allocation
+    pub fn is_success(&self) -> bool {
+        match self.evaluation() {
+            Some(eval) => {
+                eval.status == EVAL_STATUS_COMPLETE && self.placement_failures().is_empty()
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct EvalMonitor<'a> {
+    client: &'a NomadClient,
+    eval_id: String,
+    options: MonitorOptions,
+}
+
+impl<'a> EvalMonitor<'a> {
+    pub(crate) fn new(client: &'a NomadClient, eval_id: &str, options: MonitorOptions) -> Self {
+        EvalMonitor {
+            client,
+            eval_id: eval_id.to_string(),
+            options,
+        }
+    }
+
+    //
+    // Follow the evaluation and any `next_eval` it hands off to until a
+    // terminal status is reached. A `blocked_eval` is read once and reported
+    // but not waited upon; it only progresses when cluster capacity changes.
+    //
+    pub async fn run(self) -> Result<EvalReport> {
+        let deadline = self.options.timeout.map(|t| Instant::now() + t);
+        let mut report = EvalReport::default();
+        let mut eval_id = self.eval_id.clone();
+
+        loop {
+            let eval = self.wait_terminal(&eval_id, deadline).await?;
+            if !eval.blocked_eval.is_empty() && report.blocked_eval.is_none() {
+                let (blocked, _) = self
+                    .client
+                    .evaluations()
+                    .info(&eval.blocked_eval, &QueryOptions::default())
+                    .await?;
+                report.blocked_eval = Some(blocked);
+            }
+            let next_eval = eval.next_eval.clone();
+            report.evaluations.push(eval);
+            if next_eval.is_empty() {
+                break;
+            }
+            eval_id = next_eval;
+        }
+
+        for eval in report.evaluations.iter() {
+            let (mut allocs, _) = self
+                .client
+                .evaluations()
+                .allocations(&eval.id, &QueryOptions::default())
+                .await?;
+            report.allocations.append(&mut allocs);
+        }
+
+        Ok(report)
+    }
+
+    async fn wait_terminal(&self, eval_id: &str, deadline: Option<Instant>) -> Result<Evaluation> {
+        let mut index = 0;
+        loop {
+            let (eval, meta) = self
+                .client
+                .evaluations()
+                .info(eval_id, &self.options.blocking_query(index))
+                .await?;
+            if eval.is_terminal() {
+                return Ok(eval);
+            }
+            if expired(deadline) {
+                return Err(Error::Timeout(format!(
+                    "evaluation \"{}\" still {}",
+                    eval_id, eval.status
+                )));
+            }
+            self.options.pace(index, meta.last_index).await;
+            index = meta.last_index;
+        }
+    }
+}
+
+pub struct DeploymentMonitor<'a> {
+    client: &'a NomadClient,
+    deployment_id: String,
+    options: MonitorOptions,
+}
+
+impl<'a> DeploymentMonitor<'a> {
+    pub(crate) fn new(
+        client: &'a NomadClient,
+        deployment_id: &str,
+        options: MonitorOptions,
+    ) -> Self {
+        DeploymentMonitor {
+            client,
+            deployment_id: deployment_id.to_string(),
+            options,
+        }
+    }
+
+    //
+    // Wait for the deployment to reach a terminal status, returning its final
+    // state. A paused deployment is waited upon like a running one.
+    //
+    pub async fn run(self) -> Result<Deployment> {
+        let deadline = self.options.timeout.map(|t| Instant::now() + t);
+        let mut index = 0;
+        loop {
+            let (deployment, meta) = self
+                .client
+                .deployments()
+                .info(&self.deployment_id, &self.options.blocking_query(index))
+                .await?;
+            if deployment.is_terminal() {
+                return Ok(deployment);
+            }
+            if expired(deadline) {
+                return Err(Error::Timeout(format!(
+                    "deployment \"{}\" still {}",
+                    self.deployment_id, deployment.status
+                )));
+            }
+            self.options.pace(index, meta.last_index).await;
+            index = meta.last_index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::evaluations::tests::eval_json;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast() -> MonitorOptions {
+        MonitorOptions {
+            wait_time: Duration::from_millis(10),
+            poll_interval: Duration::from_millis(1),
+            timeout: Some(Duration::from_secs(5)),
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_next_eval() {
+        let polls = AtomicUsize::new(0);
+        let address = serve(move |request| match request.path.as_str() {
+            "/v1/evaluation/first" => {
+                if polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    StubResponse::json(&eval_json("first", "pending")).index(40)
+                } else {
+                    let mut eval = eval_json("first", "complete");
+                    eval["NextEval"] = json!("second");
+                    StubResponse::json(&eval).index(41)
+                }
+            }
+            "/v1/evaluation/second" => {
+                let mut eval = eval_json("second", "complete");
+                eval["DeploymentID"] = json!("d1");
+                StubResponse::json(&eval).index(42)
+            }
+            "/v1/evaluation/second/allocations" => {
+                StubResponse::json(&json!([{"ID": "a1", "ClientStatus": "pending"}]))
+            }
+            _ => StubResponse::json(&json!([])),
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let report = client
+            .evaluations()
+            .monitor("first", fast())
+            .run()
+            .await
+            .expect("monitor");
+        assert_eq!(report.evaluations.len(), 2);
+        assert_eq!(report.allocations.len(), 1);
+        assert_eq!(report.deployment_id(), Some("d1"));
+        assert!(report.is_success());
+    }
+
+    #[tokio::test]
+    async fn reports_placement_failures() {
+        let address = serve(|request| match request.path.as_str() {
+            "/v1/evaluation/first" => {
+                let mut eval = eval_json("first", "complete");
+                eval["BlockedEval"] = json!("blocked");
+                eval["FailedTGAllocs"] = json!({
+                    "cache": {
+                        "NodesEvaluated": 3,
+                        "NodesFiltered": 3,
+                        "NodesAvailable": {"dc1": 3},
+                        "ClassFiltered": null,
+                        "ConstraintFiltered": {"${attr.kernel.name} = linux": 3},
+                        "NodesExhausted": 0,
+                        "ClassExhausted": null,
+                        "DimensionExhausted": null,
+                        "QuotaExhausted": null
+                    }
+                });
+                StubResponse::json(&eval).index(40)
+            }
+            "/v1/evaluation/blocked" => {
+                StubResponse::json(&eval_json("blocked", "blocked")).index(41)
+            }
+            _ => StubResponse::json(&json!([])),
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let report = client
+            .evaluations()
+            .monitor("first", fast())
+            .run()
+            .await
+            .expect("monitor");
+        assert!(!report.is_success());
+        assert_eq!(
+            report.blocked_eval.as_ref().map(|e| e.status.as_str()),
+            Some("blocked")
+        );
+        let failures = report.placement_failures();
+        assert_eq!(failures["cache"].nodes_filtered, 3);
+        assert_eq!(
+            report.explain_failures(),
+            [
+                "Task Group \"cache\" (failed to place 1 allocation):",
+                "  * Constraint \"${attr.kernel.name} = linux\": 3 nodes excluded by filter",
+                "Evaluation \"blocked\" waiting for additional capacity to place remainder",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn times_out() {
+        let address = serve(|_| StubResponse::json(&eval_json("first", "pending")).index(40)).await;
+        let client = NomadClient::new(&address);
+        let options = MonitorOptions {
+            timeout: Some(Duration::from_millis(20)),
+            ..fast()
+        };
+        let result = client.evaluations().monitor("first", options).run().await;
+        assert!(matches!(result, Err(Error::Timeout(_))));
+    }
+}
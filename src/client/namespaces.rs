@@ -0,0 +1,96 @@
+use reqwest::Method;
+
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions, WriteMeta};
+use crate::error::Result;
+use crate::model::namespaces::Namespace;
+
+pub struct Namespaces<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Namespaces<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Namespaces { client }
+    }
+
+    pub async fn list(&self, options: &QueryOptions) -> Result<(Vec<Namespace>, QueryMeta)> {
+        self.client.query("/v1/namespaces", options).await
+    }
+
+    pub async fn info(&self, name: &str, options: &QueryOptions) -> Result<(Namespace, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/namespace/{}", segment(name)), options)
+            .await
+    }
+
+    // Create the namespace or update it if it already exists
+    pub async fn upsert(&self, namespace: &Namespace) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta(
+                Method::POST,
+                &format!("/v1/namespace/{}", segment(&namespace.name)),
+                &[],
+                Some(namespace),
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/namespace/{}", segment(name)),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn upsert_and_delete() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/namespace/api-prod");
+            match request.method.as_str() {
+                "POST" => {
+                    let body: serde_json::Value =
+                        serde_json::from_slice(&request.body).expect("namespace body");
+                    assert_eq!(body["Description"], json!("Production API Servers"));
+                    StubResponse::raw(200, b"").index(31)
+                }
+                "DELETE" => StubResponse::raw(200, b"").index(32),
+                _ => StubResponse::raw(405, b"method not allowed"),
+            }
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let namespace = Namespace {
+            name: "api-prod".to_string(),
+            description: "Production API Servers".to_string(),
+            ..Default::default()
+        };
+        let meta = client
+            .namespaces()
+            .upsert(&namespace)
+            .await
+            .expect("upsert");
+        assert_eq!(meta.last_index, 31);
+        let meta = client
+            .namespaces()
+            .delete("api-prod")
+            .await
+            .expect("delete");
+        assert_eq!(meta.last_index, 32);
+    }
+}
@@ -0,0 +1,352 @@
+use reqwest::{Body, Method};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+
+use crate::client::{NomadClient, QueryMeta, QueryOptions, WriteMeta};
+use crate::error::{Error, Result};
+use crate::model::operator::{
+    AutopilotConfiguration, OperatorHealthReply, RaftConfiguration, SchedulerConfiguration,
+    SchedulerConfigurationResponse, SchedulerSetConfigurationResponse,
+};
+
+pub struct Operator<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Operator<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Operator { client }
+    }
+
+    //
+    // Raft
+    //
+
+    pub async fn raft_configuration(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(RaftConfiguration, QueryMeta)> {
+        self.client
+            .query("/v1/operator/raft/configuration", options)
+            .await
+    }
+
+    // Remove a failed server from the raft peers by its "ip:port" address
+    pub async fn raft_remove_peer_by_address(&self, address: &str) -> Result<()> {
+        self.raft_remove_peer(("address", address)).await
+    }
+
+    // Remove a failed server from the raft peers by its raft ID
+    pub async fn raft_remove_peer_by_id(&self, id: &str) -> Result<()> {
+        self.raft_remove_peer(("id", id)).await
+    }
+
+    async fn raft_remove_peer(&self, (key, value): (&str, &str)) -> Result<()> {
+        self.client
+            .write::<(), _>(
+                Method::DELETE,
+                "/v1/operator/raft/peer",
+                &[(key, value.to_string())],
+                None,
+            )
+            .await
+    }
+
+    //
+    // Autopilot
+    //
+
+    pub async fn autopilot_configuration(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(AutopilotConfiguration, QueryMeta)> {
+        self.client
+            .query("/v1/operator/autopilot/configuration", options)
+            .await
+    }
+
+    pub async fn set_autopilot_configuration(&self, config: &AutopilotConfiguration) -> Result<()> {
+        self.client
+            .write::<_, bool>(
+                Method::PUT,
+                "/v1/operator/autopilot/configuration",
+                &[],
+                Some(config),
+            )
+            .await?;
+        Ok(())
+    }
+
+    //
+    // Update the configuration only if it has not been modified since
+    // `modify_index`, returning whether the update was applied
+    //
+    pub async fn cas_autopilot_configuration(
+        &self,
+        config: &AutopilotConfiguration,
+        modify_index: u64,
+    ) -> Result<bool> {
+        self.client
+            .write(
+                Method::PUT,
+                "/v1/operator/autopilot/configuration",
+                &[("cas", modify_index.to_string())],
+                Some(config),
+            )
+            .await
+    }
+
+    pub async fn autopilot_health(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(OperatorHealthReply, QueryMeta)> {
+        self.client
+            .query("/v1/operator/autopilot/health", options)
+            .await
+    }
+
+    //
+    // Scheduler
+    //
+
+    pub async fn scheduler_configuration(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(SchedulerConfiguration, QueryMeta)> {
+        let (response, meta): (SchedulerConfigurationResponse, _) = self
+            .client
+            .query("/v1/operator/scheduler/configuration", options)
+            .await?;
+        let config = response.scheduler_config.ok_or_else(|| {
+            Error::UnexpectedResponse("missing scheduler configuration".to_string())
+        })?;
+        Ok((config, meta))
+    }
+
+    pub async fn set_scheduler_configuration(
+        &self,
+        config: &SchedulerConfiguration,
+    ) -> Result<SchedulerSetConfigurationResponse> {
+        self.client
+            .write(
+                Method::PUT,
+                "/v1/operator/scheduler/configuration",
+                &[],
+                Some(config),
+            )
+            .await
+    }
+
+    //
+    // Update the configuration only if it has not been modified since
+    // `modify_index`; `updated` in the response is false when it has
+    //
+    pub async fn cas_scheduler_configuration(
+        &self,
+        config: &SchedulerConfiguration,
+        modify_index: u64,
+    ) -> Result<SchedulerSetConfigurationResponse> {
+        self.client
+            .write(
+                Method::PUT,
+                "/v1/operator/scheduler/configuration",
+                &[("cas", modify_index.to_string())],
+                Some(config),
+            )
+            .await
+    }
+
+    //
+    // Snapshots
+    //
+
+    //
+    // Stream a snapshot of the cluster state to `writer` without buffering
+    // it in memory. The data is checked against the `Digest` header sent by
+    // the agent and an `Error::Integrity` is returned if they differ, in
+    // which case the written snapshot must be discarded.
+    //
+    pub async fn snapshot_save<W>(
+        &self,
+        writer: &mut W,
+        options: &QueryOptions,
+    ) -> Result<QueryMeta>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let builder =
+            self.client
+                .request(Method::GET, "/v1/operator/snapshot", &options.to_pairs());
+        let mut response = self.client.send(builder).await?;
+        let meta = QueryMeta::from_headers(response.headers());
+        let expected = response
+            .headers()
+            .get("Digest")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+            .ok_or_else(|| Error::Integrity("snapshot response has no digest".to_string()))?;
+
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = response.chunk().await? {
+            hasher.update(&chunk);
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+
+        let actual = format!("sha-256={}", base64::encode(hasher.finalize()));
+        if !expected
+            .split(',')
+            .any(|digest| digest.trim().eq_ignore_ascii_case(&actual))
+        {
+            return Err(Error::Integrity(format!(
+                "snapshot digest {} does not match {}",
+                actual, expected
+            )));
+        }
+        Ok(meta)
+    }
+
+    //
+    // Restore the cluster state from a snapshot streamed from `reader`.
+    // `force` restores even if the snapshot is older than the current state.
+    //
+    pub async fn snapshot_restore<R>(&self, reader: R, force: bool) -> Result<WriteMeta>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        let mut params = Vec::new();
+        if force {
+            params.push(("force", "true".to_string()));
+        }
+        let builder = self
+            .client
+            .request(Method::PUT, "/v1/operator/snapshot", &params)
+            .body(Body::wrap_stream(ReaderStream::new(reader)));
+        let response = self.client.send(builder).await?;
+        Ok(WriteMeta {
+            last_index: QueryMeta::from_headers(response.headers()).last_index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use crate::model::operator::SCHEDULER_ALGORITHM_SPREAD;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn cas_scheduler_configuration() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/operator/scheduler/configuration");
+            match request.method.as_str() {
+                "GET" => StubResponse::json(&json!({
+                    "SchedulerConfig": {"SchedulerAlgorithm": "binpack", "ModifyIndex": 7}
+                })),
+                "PUT" => {
+                    let body: serde_json::Value =
+                        serde_json::from_slice(&request.body).expect("body");
+                    assert_eq!(body["SchedulerAlgorithm"], "spread");
+                    let updated = request.query["cas"] == "7";
+                    StubResponse::json(&json!({"Updated": updated, "Index": 8}))
+                }
+                _ => StubResponse::raw(405, b"method not allowed"),
+            }
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let (mut config, _) = client
+            .operator()
+            .scheduler_configuration(&QueryOptions::default())
+            .await
+            .expect("get");
+        config.scheduler_algorithm = SCHEDULER_ALGORITHM_SPREAD.to_string();
+        let response = client
+            .operator()
+            .cas_scheduler_configuration(&config, config.modify_index)
+            .await
+            .expect("cas");
+        assert!(response.updated);
+        let response = client
+            .operator()
+            .cas_scheduler_configuration(&config, 3)
+            .await
+            .expect("cas");
+        assert!(!response.updated);
+    }
+
+    async fn snapshot_server(data: Vec<u8>, digest: String) -> String {
+        serve(move |request| {
+            assert_eq!(request.path, "/v1/operator/snapshot");
+            StubResponse::raw(200, &data)
+                .header("Digest", &digest)
+                .index(42)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn snapshot_save_verifies_digest() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let digest = format!("sha-256={}", base64::encode(Sha256::digest(&data)));
+
+        let client = NomadClient::new(&snapshot_server(data.clone(), digest).await);
+        let mut saved = Vec::new();
+        let meta = client
+            .operator()
+            .snapshot_save(&mut saved, &QueryOptions::default())
+            .await
+            .expect("save");
+        assert_eq!(meta.last_index, 42);
+        assert_eq!(saved, data);
+
+        let corrupt = format!("sha-256={}", base64::encode(Sha256::digest(b"other")));
+        let client = NomadClient::new(&snapshot_server(data, corrupt).await);
+        let result = client
+            .operator()
+            .snapshot_save(&mut Vec::new(), &QueryOptions::default())
+            .await;
+        assert!(matches!(result, Err(Error::Integrity(_))));
+    }
+
+    #[tokio::test]
+    async fn snapshot_restore_streams_body() {
+        let address = serve(|request| {
+            assert_eq!(request.method, "PUT");
+            assert_eq!(request.path, "/v1/operator/snapshot");
+            assert_eq!(request.query["force"], "true");
+            assert_eq!(request.body, b"snapshot-data");
+            StubResponse::raw(200, b"").index(9)
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let meta = client
+            .operator()
+            .snapshot_restore(&b"snapshot-data"[..], true)
+            .await
+            .expect("restore");
+        assert_eq!(meta.last_index, 9);
+    }
+
+    #[tokio::test]
+    async fn remove_raft_peer() {
+        let address = serve(|request| {
+            assert_eq!(request.method, "DELETE");
+            assert_eq!(request.path, "/v1/operator/raft/peer");
+            assert_eq!(request.query["address"], "10.0.0.3:4647");
+            StubResponse::raw(200, b"")
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        client
+            .operator()
+            .raft_remove_peer_by_address("10.0.0.3:4647")
+            .await
+            .expect("remove peer");
+    }
+}
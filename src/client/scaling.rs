@@ -0,0 +1,91 @@
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions};
+use crate::error::Result;
+use crate::model::scaling::{ScalingPolicy, ScalingPolicyListStub};
+
+// ScalingPolicyFilter narrows a policy listing to a job and/or policy type
+#[derive(Debug, Default, Clone)]
+pub struct ScalingPolicyFilter {
+    pub job: Option<String>,
+    pub policy_type: Option<String>,
+}
+
+impl ScalingPolicyFilter {
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(ref job) = self.job {
+            params.push(("job", job.clone()));
+        }
+        if let Some(ref policy_type) = self.policy_type {
+            params.push(("type", policy_type.clone()));
+        }
+        params
+    }
+}
+
+pub struct Scaling<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Scaling<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Scaling { client }
+    }
+
+    pub async fn policies(
+        &self,
+        filter: &ScalingPolicyFilter,
+        options: &QueryOptions,
+    ) -> Result<(Vec<ScalingPolicyListStub>, QueryMeta)> {
+        self.client
+            .query_with("/v1/scaling/policies", &filter.to_params(), options)
+            .await
+    }
+
+    pub async fn policy(
+        &self,
+        id: &str,
+        options: &QueryOptions,
+    ) -> Result<(ScalingPolicy, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/scaling/policy/{}", segment(id)), options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn list_policies_for_job() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/scaling/policies");
+            assert_eq!(request.query.get("job").map(String::as_str), Some("webapp"));
+            StubResponse::json(&json!([{
+                "ID": "5e9772e6-6f37-4b66-a0f9-b2e3a4e4bc57",
+                "Enabled": true,
+                "Type": "horizontal",
+                "Target": {"Namespace": "default", "Job": "webapp", "Group": "demo"},
+                "CreateIndex": 10,
+                "ModifyIndex": 10
+            }]))
+            .index(10)
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let filter = ScalingPolicyFilter {
+            job: Some("webapp".to_string()),
+            ..Default::default()
+        };
+        let (policies, _) = client
+            .scaling()
+            .policies(&filter, &QueryOptions::default())
+            .await
+            .expect("list policies");
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].target.job.as_deref(), Some("webapp"));
+    }
+}
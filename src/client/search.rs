@@ -0,0 +1,205 @@
+use crate::client::{NomadClient, QueryMeta, QueryOptions};
+use crate::error::{Error, Result};
+use crate::model::search::{
+    Context, FuzzySearchRequest, FuzzySearchResponse, SearchRequest, SearchResponse,
+};
+
+pub struct Search<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Search<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Search { client }
+    }
+
+    // Find the IDs in `context` starting with `prefix`
+    pub async fn prefix(
+        &self,
+        prefix: &str,
+        context: Context,
+        options: &QueryOptions,
+    ) -> Result<(SearchResponse, QueryMeta)> {
+        let request = SearchRequest {
+            prefix: prefix.to_string(),
+            context,
+        };
+        self.client
+            .query_body("/v1/search", &request, options)
+            .await
+    }
+
+    // Find the objects in `context` whose name contains `text`
+    pub async fn fuzzy(
+        &self,
+        text: &str,
+        context: Context,
+        options: &QueryOptions,
+    ) -> Result<(FuzzySearchResponse, QueryMeta)> {
+        let request = FuzzySearchRequest {
+            text: text.to_string(),
+            context,
+        };
+        self.client
+            .query_body("/v1/search/fuzzy", &request, options)
+            .await
+    }
+
+    //
+    // Resolve a short prefix to a single full ID the way the cli does for
+    // `nomad alloc status 8f3a`. Returns `Error::Ambiguous` when the prefix
+    // matches several objects, or more than the server returned, and
+    // `Error::NotFound` when it matches none.
+    //
+    pub async fn resolve(
+        &self,
+        prefix: &str,
+        context: Context,
+        options: &QueryOptions,
+    ) -> Result<String> {
+        // UUID prefixes are searched as whole bytes; an odd trailing digit
+        // is matched locally. Hyphens are not digits so are not counted.
+        let mut search = prefix;
+        if context.is_uuid() {
+            let digits = search.chars().filter(|c| *c != '-').count();
+            if let Some(last) = search.chars().last().filter(|_| digits % 2 == 1) {
+                search = &search[..search.len() - last.len_utf8()];
+            }
+            search = search.trim_end_matches('-');
+        }
+
+        let (response, _) = self.prefix(search, context, options).await?;
+        let matches: Vec<String> = response
+            .matches(context)
+            .iter()
+            .filter(|id| id.starts_with(prefix))
+            .cloned()
+            .collect();
+        if matches.iter().any(|id| id == prefix) {
+            return Ok(prefix.to_string());
+        }
+        let truncated = response.is_truncated(context);
+        match matches.len() {
+            0 if !truncated => Err(Error::NotFound(format!(
+                "no {} with prefix \"{}\"",
+                context, prefix
+            ))),
+            1 if !truncated => Ok(matches[0].clone()),
+            _ => Err(Error::Ambiguous {
+                prefix: prefix.to_string(),
+                matches,
+                truncated,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn resolve_prefix() {
+        let address = serve(|request| {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/v1/search");
+            let body: serde_json::Value = serde_json::from_slice(&request.body).expect("body");
+            assert_eq!(body["Context"], "allocs");
+            let all = [
+                "8f3a1b2c-1111-0000-0000-000000000000",
+                "8f3b9d00-2222-0000-0000-000000000000",
+                "8f3b9d11-3333-0000-0000-000000000000",
+            ];
+            let prefix = body["Prefix"].as_str().unwrap_or_default();
+            // The server only matches UUID prefixes of whole bytes
+            if prefix.chars().filter(|c| *c != '-').count() % 2 == 1 {
+                return StubResponse::json(&json!({"Matches": {"allocs": null}}));
+            }
+            let matches: Vec<&str> = all
+                .iter()
+                .copied()
+                .filter(|id| id.starts_with(prefix))
+                .collect();
+            StubResponse::json(&json!({
+                "Matches": {"allocs": matches},
+                "Truncations": {"allocs": false}
+            }))
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let options = QueryOptions::default();
+        let id = client
+            .search()
+            .resolve("8f3a", Context::Allocs, &options)
+            .await
+            .expect("resolve");
+        assert_eq!(id, "8f3a1b2c-1111-0000-0000-000000000000");
+
+        // An odd length prefix is trimmed for the search and matched locally
+        let id = client
+            .search()
+            .resolve("8f3b9d1", Context::Allocs, &options)
+            .await
+            .expect("resolve");
+        assert_eq!(id, "8f3b9d11-3333-0000-0000-000000000000");
+
+        // The hyphen is not counted so "8f3a1b2c-1" has an odd digit
+        let id = client
+            .search()
+            .resolve("8f3a1b2c-1", Context::Allocs, &options)
+            .await
+            .expect("resolve");
+        assert_eq!(id, "8f3a1b2c-1111-0000-0000-000000000000");
+
+        match client
+            .search()
+            .resolve("8f3b", Context::Allocs, &options)
+            .await
+        {
+            Err(Error::Ambiguous {
+                matches, truncated, ..
+            }) => {
+                assert_eq!(matches.len(), 2);
+                assert!(!truncated);
+            }
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+        match client
+            .search()
+            .resolve("ffff", Context::Allocs, &options)
+            .await
+        {
+            Err(Error::NotFound(_)) => {}
+            other => panic!("expected not found, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_truncated_prefix() {
+        let address = serve(|_| {
+            StubResponse::json(&json!({
+                "Matches": {"deployment": ["8f3a1b2c-1111-0000-0000-000000000000"]},
+                "Truncations": {"deployment": true}
+            }))
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        match client
+            .search()
+            .resolve("8f", Context::Deployments, &QueryOptions::default())
+            .await
+        {
+            Err(Error::Ambiguous {
+                matches, truncated, ..
+            }) => {
+                assert_eq!(matches.len(), 1);
+                assert!(truncated);
+            }
+            other => panic!("expected truncated matches, got {:?}", other),
+        }
+    }
+}
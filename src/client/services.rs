@@ -0,0 +1,253 @@
+use reqwest::Method;
+use tokio::time::Instant;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::client::monitor::{expired, MonitorOptions};
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions, WriteMeta};
+use crate::error::{Error, Result};
+use crate::model::services::{
+    ServiceRegistration, ServiceRegistrationListStub, CHECK_MODE_READINESS, CHECK_STATUS_SUCCESS,
+};
+
+pub struct Services<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Services<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Services { client }
+    }
+
+    pub async fn list(
+        &self,
+        options: &QueryOptions,
+    ) -> Result<(Vec<ServiceRegistrationListStub>, QueryMeta)> {
+        self.client.query("/v1/services", options).await
+    }
+
+    // The registered instances of a service
+    pub async fn get(
+        &self,
+        name: &str,
+        options: &QueryOptions,
+    ) -> Result<(Vec<ServiceRegistration>, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/service/{}", segment(name)), options)
+            .await
+    }
+
+    pub async fn delete(&self, name: &str, id: &str) -> Result<WriteMeta> {
+        let ((), meta) = self
+            .client
+            .write_with_meta::<(), _>(
+                Method::DELETE,
+                &format!("/v1/service/{}/{}", segment(name), segment(id)),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(meta)
+    }
+
+    //
+    // Keep the registrations whose health checks all pass, readiness checks
+    // are ignored and an instance without checks is healthy. Registrations do
+    // not carry check results so they are read from the client of each
+    // allocation; an allocation whose checks cannot be read is unhealthy.
+    //
+    pub async fn healthy(
+        &self,
+        mut registrations: Vec<ServiceRegistration>,
+    ) -> Result<Vec<ServiceRegistration>> {
+        let allocs: HashMap<String, String> = registrations
+            .iter()
+            .map(|r| (r.alloc_id.clone(), r.namespace.clone()))
+            .collect();
+        // Allocation and service name of the failing instances
+        let mut failing: HashSet<(String, String)> = HashSet::new();
+        let mut unreadable: HashSet<String> = HashSet::new();
+        for (alloc, namespace) in allocs {
+            let options = QueryOptions {
+                namespace: Some(namespace).filter(|n| !n.is_empty()),
+                ..Default::default()
+            };
+            match self.client.allocations().checks(&alloc, &options).await {
+                Ok((checks, _)) => failing.extend(
+                    checks
+                        .into_values()
+                        .filter(|c| c.mode != CHECK_MODE_READINESS)
+                        .filter(|c| c.status != CHECK_STATUS_SUCCESS)
+                        .map(|c| (alloc.clone(), c.service)),
+                ),
+                Err(Error::Api { .. }) => {
+                    unreadable.insert(alloc);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        registrations.retain(|r| {
+            !unreadable.contains(&r.alloc_id)
+                && !failing.contains(&(r.alloc_id.clone(), r.service_name.clone()))
+        });
+        Ok(registrations)
+    }
+
+    //
+    // Follow the healthy instances of a service with blocking queries, see
+    // `ServiceWatch::next`
+    //
+    pub fn watch(&self, name: &str, options: MonitorOptions) -> ServiceWatch<'a> {
+        ServiceWatch {
+            client: self.client,
+            name: name.to_string(),
+            options,
+            index: 0,
+            healthy: Vec::new(),
+        }
+    }
+}
+
+pub struct ServiceWatch<'a> {
+    client: &'a NomadClient,
+    name: String,
+    options: MonitorOptions,
+    index: u64,
+    healthy: Vec<ServiceRegistration>,
+}
+
+impl<'a> ServiceWatch<'a> {
+    //
+    // Wait for the healthy instances of the service to change, returning the
+    // current set. The first call returns immediately. Check results are not
+    // covered by the blocking query, a check changing status is seen when the
+    // query next returns, within `wait_time`. Registrations linger when a
+    // node is lost; `ServiceResolver` also drops instances whose allocation
+    // is no longer running.
+    //
+    pub async fn next(&mut self) -> Result<Vec<ServiceRegistration>> {
+        let deadline = self.options.timeout.map(|t| Instant::now() + t);
+        loop {
+            let services = self.client.services();
+            let (registrations, meta) = services
+                .get(&self.name, &self.options.blocking_query(self.index))
+                .await?;
+            let healthy = services.healthy(registrations).await?;
+            if self.index == 0 || meta.last_index != self.index || healthy != self.healthy {
+                self.index = meta.last_index;
+                self.healthy = healthy.clone();
+                return Ok(healthy);
+            }
+            if expired(deadline) {
+                return Err(Error::Timeout(format!(
+                    "service \"{}\" unchanged since index {}",
+                    self.name, self.index
+                )));
+            }
+            self.options.pace(self.index, meta.last_index).await;
+        }
+    }
+
+    // The index of the last set of instances returned
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn registration(id: &str, address: &str) -> serde_json::Value {
+        json!({
+            "ID": id,
+            "ServiceName": "api",
+            "Namespace": "default",
+            "NodeID": "f7f7a2c9",
+            "Datacenter": "dc1",
+            "JobID": "api",
+            "AllocID": format!("alloc-{}", id),
+            "Tags": ["http"],
+            "Address": address,
+            "Port": 8080,
+            "CreateIndex": 5,
+            "ModifyIndex": 5
+        })
+    }
+
+    fn check(service: &str, mode: &str, status: &str) -> serde_json::Value {
+        json!({
+            "ID": format!("{}-{}", service, mode),
+            "Service": service,
+            "Group": "api",
+            "Mode": mode,
+            "Status": status
+        })
+    }
+
+    #[tokio::test]
+    async fn watch_service() {
+        let checks_b = Arc::new(AtomicUsize::new(0));
+        let address = serve(move |request| match request.path.as_str() {
+            "/v1/service/api" => match request.query.get("index").map(String::as_str) {
+                None => StubResponse::json(&json!([registration("a", "10.0.0.1")])).index(5),
+                _ => StubResponse::json(&json!([
+                    registration("a", "10.0.0.1"),
+                    registration("b", "10.0.0.2"),
+                    registration("c", "10.0.0.3")
+                ]))
+                .index(7),
+            },
+            // A failing readiness check does not affect health
+            "/v1/client/allocation/alloc-a/checks" => StubResponse::json(&json!({
+                "1": check("api", "healthiness", "success"),
+                "2": check("api", "readiness", "failure"),
+                "3": check("admin", "healthiness", "failure")
+            })),
+            // Passing from the second time it is read
+            "/v1/client/allocation/alloc-b/checks" => {
+                let status = match checks_b.fetch_add(1, Ordering::SeqCst) {
+                    0 => "pending",
+                    _ => "success",
+                };
+                StubResponse::json(&json!({"1": check("api", "healthiness", status)}))
+            }
+            _ => StubResponse::raw(404, b"alloc not found"),
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let mut watch = client.services().watch(
+            "api",
+            MonitorOptions {
+                poll_interval: Duration::from_millis(10),
+                timeout: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+        );
+
+        let instances = watch.next().await.expect("first");
+        assert_eq!(instances.len(), 1);
+        assert_eq!(
+            instances[0].socket_addr(),
+            Some("10.0.0.1:8080".parse().unwrap())
+        );
+
+        // The index moves on while the new instance's check is pending
+        let instances = watch.next().await.expect("second");
+        assert_eq!(instances.len(), 1);
+        assert_eq!(watch.index(), 7);
+
+        // Its check passing changes the healthy set at the same index
+        let instances = watch.next().await.expect("third");
+        let ids: Vec<&str> = instances.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        assert!(matches!(watch.next().await, Err(Error::Timeout(_))));
+    }
+}
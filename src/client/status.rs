@@ -0,0 +1,71 @@
+use crate::client::{NomadClient, QueryOptions};
+use crate::error::Result;
+
+pub struct Status<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Status<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Status { client }
+    }
+
+    //
+    // The RPC address ("ip:port") of the leader of the region, empty if the
+    // cluster has no leader
+    //
+    pub async fn leader(&self) -> Result<String> {
+        let (leader, _) = self
+            .client
+            .query("/v1/status/leader", &QueryOptions::default())
+            .await?;
+        Ok(leader)
+    }
+
+    // The RPC addresses of the raft peers in the region
+    pub async fn peers(&self) -> Result<Vec<String>> {
+        let (peers, _) = self
+            .client
+            .query("/v1/status/peers", &QueryOptions::default())
+            .await?;
+        Ok(peers)
+    }
+
+    // The names of all regions known to the cluster
+    pub async fn regions(&self) -> Result<Vec<String>> {
+        let (regions, _) = self
+            .client
+            .query("/v1/regions", &QueryOptions::default())
+            .await?;
+        Ok(regions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn leader_and_peers() {
+        let address = serve(|request| match request.path.as_str() {
+            "/v1/status/leader" => StubResponse::json(&json!("10.0.0.1:4647")),
+            "/v1/status/peers" => StubResponse::json(&json!(["10.0.0.1:4647", "10.0.0.2:4647"])),
+            "/v1/regions" => StubResponse::json(&json!(["global", "europe"])),
+            _ => StubResponse::raw(404, b"not found"),
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        assert_eq!(
+            client.status().leader().await.expect("leader"),
+            "10.0.0.1:4647"
+        );
+        assert_eq!(client.status().peers().await.expect("peers").len(), 2);
+        assert_eq!(
+            client.status().regions().await.expect("regions"),
+            vec!["global", "europe"]
+        );
+    }
+}
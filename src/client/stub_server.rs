@@ -0,0 +1,206 @@
+//
+// A minimal HTTP/1.1 server standing in for a Nomad agent in tests. Each
+// connection serves a single request which is handed to the supplied handler.
+//
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StubResponse {
+    pub fn json<T: serde::Serialize>(value: &T) -> Self {
+        StubResponse {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: serde_json::to_vec(value).expect("serialize stub response"),
+        }
+    }
+
+    pub fn raw(status: u16, body: &[u8]) -> Self {
+        StubResponse {
+            status,
+            headers: Vec::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn index(self, index: u64) -> Self {
+        self.header("X-Nomad-Index", &index.to_string())
+    }
+}
+
+fn decode(value: &str) -> String {
+    let bytes = value.replace('+', " ").into_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(b) = u8::from_str_radix(&String::from_utf8_lossy(&bytes[i + 1..i + 3]), 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Join the chunks of a body sent with chunked transfer encoding
+fn decode_chunked(mut raw: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    while let Some(i) = raw.windows(2).position(|w| w == b"\r\n") {
+        let size = String::from_utf8_lossy(&raw[..i]);
+        let size = usize::from_str_radix(size.trim(), 16).unwrap_or(0);
+        if size == 0 || raw.len() < i + 2 + size {
+            break;
+        }
+        body.extend_from_slice(&raw[i + 2..i + 2 + size]);
+        raw = &raw[(i + 4 + size).min(raw.len())..];
+    }
+    body
+}
+
+fn parse_head(
+    head: &str,
+) -> (
+    String,
+    String,
+    HashMap<String, String>,
+    HashMap<String, String>,
+) {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+
+    let (path, query_string) = match target.find('?') {
+        Some(i) => (&target[..i], &target[i + 1..]),
+        None => (target, ""),
+    };
+    let query = query_string
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| match kv.find('=') {
+            Some(i) => (decode(&kv[..i]), decode(&kv[i + 1..])),
+            None => (decode(kv), String::new()),
+        })
+        .collect();
+    let headers = lines
+        .filter_map(|line| {
+            line.find(':').map(|i| {
+                (
+                    line[..i].trim().to_lowercase(),
+                    line[i + 1..].trim().to_string(),
+                )
+            })
+        })
+        .collect();
+
+    (method, decode(path), query, headers)
+}
+
+//
+// Start serving on an ephemeral port, returning the base address
+//
+pub async fn serve<F>(handler: F) -> String
+where
+    F: Fn(&StubRequest) -> StubResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let address = format!("http://{}", listener.local_addr().expect("local addr"));
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(_) => return,
+            };
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut buffer = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let head_end = loop {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        return;
+                    }
+                    buffer.extend_from_slice(&chunk[..n]);
+                    if let Some(i) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break i;
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+                let (method, path, query, headers) = parse_head(&head);
+                let length = headers
+                    .get("content-length")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(0);
+                let chunked = headers
+                    .get("transfer-encoding")
+                    .is_some_and(|v| v.eq_ignore_ascii_case("chunked"));
+                let mut body = buffer[head_end + 4..].to_vec();
+                while (chunked && !body.ends_with(b"0\r\n\r\n")) || body.len() < length {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    body.extend_from_slice(&chunk[..n]);
+                }
+                if chunked {
+                    body = decode_chunked(&body);
+                }
+
+                let request = StubRequest {
+                    method,
+                    path,
+                    query,
+                    headers,
+                    body,
+                };
+                let response = handler(&request);
+
+                let mut out = format!("HTTP/1.1 {} Stub\r\n", response.status);
+                for (name, value) in response.headers.iter() {
+                    out.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                out.push_str(&format!(
+                    "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    response.body.len()
+                ));
+                let _ = socket.write_all(out.as_bytes()).await;
+                let _ = socket.write_all(&response.body).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+
+    address
+}
@@ -0,0 +1,186 @@
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+
+use crate::client::{segment, NomadClient, QueryMeta, QueryOptions, WriteMeta};
+use crate::error::{Error, Result};
+use crate::model::variables::{Variable, VariableMetadata};
+
+pub struct Variables<'a> {
+    client: &'a NomadClient,
+}
+
+impl<'a> Variables<'a> {
+    pub(crate) fn new(client: &'a NomadClient) -> Self {
+        Variables { client }
+    }
+
+    // List variable metadata, `QueryOptions::prefix` filters on the path
+    pub async fn list(&self, options: &QueryOptions) -> Result<(Vec<VariableMetadata>, QueryMeta)> {
+        self.client.query("/v1/vars", options).await
+    }
+
+    pub async fn read(&self, path: &str, options: &QueryOptions) -> Result<(Variable, QueryMeta)> {
+        self.client
+            .query(&format!("/v1/var/{}", segment(path)), options)
+            .await
+    }
+
+    //
+    // Write the variable if it has not changed since it was read, using its
+    // `modify_index` for check-and-set. A new variable has an index of 0 so is
+    // only written if nothing exists at the path yet. A lost race returns
+    // `Error::Conflict` holding the server's current copy.
+    //
+    pub async fn put(&self, variable: &Variable) -> Result<(Variable, WriteMeta)> {
+        let params = self.params(variable, "cas", variable.modify_index.to_string());
+        self.send_cas(Method::PUT, variable, params, Some(variable))
+            .await
+    }
+
+    //
+    // Delete the variable if it has not changed since it was read, see `put`
+    //
+    pub async fn delete(&self, variable: &Variable) -> Result<WriteMeta> {
+        let params = self.params(variable, "cas", variable.modify_index.to_string());
+        let ((), meta) = self
+            .send_cas(Method::DELETE, variable, params, None)
+            .await?;
+        Ok(meta)
+    }
+
+    //
+    // Locks
+    //
+
+    //
+    // Acquire the lock on a variable, creating the variable if needed. The
+    // TTL and lock delay are taken from `variable.lock`. The returned variable
+    // holds the lock ID needed to renew and release the lock.
+    //
+    pub async fn acquire_lock(&self, variable: &Variable) -> Result<(Variable, WriteMeta)> {
+        let params = self.params(variable, "lock-acquire", String::new());
+        self.send_cas(Method::PUT, variable, params, Some(variable))
+            .await
+    }
+
+    // Extend a held lock for another TTL
+    pub async fn renew_lock(&self, variable: &Variable) -> Result<(VariableMetadata, WriteMeta)> {
+        self.check_lock(variable)?;
+        let params = self.params(variable, "lock-renew", String::new());
+        self.send_cas(Method::PUT, variable, params, Some(variable))
+            .await
+    }
+
+    pub async fn release_lock(&self, variable: &Variable) -> Result<(Variable, WriteMeta)> {
+        self.check_lock(variable)?;
+        let params = self.params(variable, "lock-release", String::new());
+        self.send_cas(Method::PUT, variable, params, Some(variable))
+            .await
+    }
+
+    fn check_lock(&self, variable: &Variable) -> Result<()> {
+        if variable.is_locked() {
+            Ok(())
+        } else {
+            Err(Error::InvalidRequest(format!(
+                "variable \"{}\" does not hold a lock",
+                variable.path
+            )))
+        }
+    }
+
+    fn params(
+        &self,
+        variable: &Variable,
+        key: &'static str,
+        value: String,
+    ) -> Vec<(&'static str, String)> {
+        let mut params = vec![(key, value)];
+        if !variable.namespace.is_empty() {
+            params.push(("namespace", variable.namespace.clone()));
+        }
+        params
+    }
+
+    //
+    // The server answers a conflicting write with 409 and the variable it
+    // holds, which is surfaced as `Error::Conflict`
+    //
+    async fn send_cas<T>(
+        &self,
+        method: Method,
+        variable: &Variable,
+        params: Vec<(&'static str, String)>,
+        body: Option<&Variable>,
+    ) -> Result<(T, WriteMeta)>
+    where
+        T: DeserializeOwned,
+    {
+        if variable.path.is_empty() {
+            return Err(Error::InvalidRequest("missing variable path".to_string()));
+        }
+        let path = format!("/v1/var/{}", segment(&variable.path));
+        match self.client.write_cas(method, &path, &params, body).await? {
+            Ok(written) => Ok(written),
+            Err(current) => {
+                let current: Variable = serde_json::from_slice(&current)?;
+                Err(Error::Conflict(Box::new(current)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn put_with_cas() {
+        let address = serve(|request| {
+            assert_eq!(request.method, "PUT");
+            assert_eq!(request.path, "/v1/var/app/config");
+            let body: serde_json::Value =
+                serde_json::from_slice(&request.body).expect("variable body");
+            assert_eq!(body["Items"]["db_password"], json!("hunter2"));
+            match request.query.get("cas").map(String::as_str) {
+                Some("0") => StubResponse::json(&json!({
+                    "Path": "app/config",
+                    "Namespace": "default",
+                    "Items": body["Items"],
+                    "ModifyIndex": 10
+                }))
+                .index(10),
+                _ => StubResponse::raw(
+                    409,
+                    json!({
+                        "Path": "app/config",
+                        "Namespace": "default",
+                        "ModifyIndex": 12
+                    })
+                    .to_string()
+                    .as_bytes(),
+                ),
+            }
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let variable = Variable::new("app/config").with_item("db_password", "hunter2");
+        let (written, meta) = client.variables().put(&variable).await.expect("put");
+        assert_eq!(meta.last_index, 10);
+        assert_eq!(written.modify_index, 10);
+
+        match client.variables().put(&written).await {
+            Err(Error::Conflict(current)) => assert_eq!(current.modify_index, 12),
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+
+        let result = client
+            .variables()
+            .renew_lock(&Variable::new("app/config"))
+            .await;
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+}
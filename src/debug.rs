@@ -0,0 +1,519 @@
+//
+// Capture the state of a cluster into a gzipped tarball in the spirit of
+// `nomad operator debug`; the artifact attached to support tickets
+//
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::future::join_all;
+use serde::Serialize;
+use tokio::time::{sleep_until, timeout_at, Instant};
+
+use std::io::Write;
+use std::time::Duration;
+
+use crate::client::agent::{LogMonitorOptions, Profile, ProfileOptions};
+use crate::client::{NomadClient, QueryOptions};
+use crate::error::{Error, Result};
+use crate::model::namespaces::ALL_NAMESPACES;
+
+// Target every server or client node in `DebugOptions`
+pub const DEBUG_TARGET_ALL: &str = "all";
+
+// Substrings of JSON keys whose string values are replaced in the bundle
+const REDACTED_KEYS: &[&str] = &["token", "secret", "password", "encrypt"];
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Clone)]
+pub struct DebugOptions {
+    // How long to collect logs, metrics and cpu profiles for
+    pub duration: Duration,
+    // How often metrics are sampled during `duration`
+    pub interval: Duration,
+    // Server names (or "leader", "all") to collect logs and profiles from
+    pub servers: Vec<String>,
+    // Client node IDs (or "all") to collect logs and profiles from
+    pub nodes: Vec<String>,
+    pub log_level: String,
+    pub profiles: bool,
+}
+
+impl Default for DebugOptions {
+    fn default() -> Self {
+        DebugOptions {
+            duration: Duration::from_secs(120),
+            interval: Duration::from_secs(30),
+            servers: vec!["leader".to_string()],
+            nodes: Vec::new(),
+            log_level: "debug".to_string(),
+            profiles: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestError {
+    pub path: String,
+    pub error: String,
+}
+
+// Manifest describes the bundle and is written to it as `manifest.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub address: String,
+    pub region: Option<String>,
+    pub duration_secs: u64,
+    pub interval_secs: u64,
+    pub servers: Vec<String>,
+    pub nodes: Vec<String>,
+    pub files: Vec<ManifestEntry>,
+    // Captures which failed; a partial bundle is still useful
+    pub errors: Vec<ManifestError>,
+}
+
+struct Bundle<W: Write> {
+    tar: tar::Builder<GzEncoder<W>>,
+    prefix: String,
+    manifest: Manifest,
+}
+
+impl<W: Write> Bundle<W> {
+    fn add(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        self.tar
+            .append_data(&mut header, format!("{}/{}", self.prefix, path), data)?;
+        self.manifest.files.push(ManifestEntry {
+            path: path.to_string(),
+            size: data.len() as u64,
+        });
+        Ok(())
+    }
+
+    fn add_json(&mut self, path: &str, mut value: serde_json::Value) -> Result<()> {
+        redact(&mut value);
+        let data = serde_json::to_vec_pretty(&value)?;
+        self.add(path, &data)
+    }
+
+    // Add a capture, recording rather than failing on errors
+    fn record<T>(&mut self, path: &str, capture: Result<T>) -> Result<()>
+    where
+        T: Into<Capture>,
+    {
+        match capture.map(Into::into) {
+            Ok(Capture::Json(value)) => self.add_json(path, value),
+            Ok(Capture::Bytes(data)) => self.add(path, &data),
+            Err(e) => {
+                self.manifest.errors.push(ManifestError {
+                    path: path.to_string(),
+                    error: e.to_string(),
+                });
+                Ok(())
+            }
+        }
+    }
+}
+
+enum Capture {
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+}
+
+impl From<serde_json::Value> for Capture {
+    fn from(value: serde_json::Value) -> Self {
+        Capture::Json(value)
+    }
+}
+
+impl From<Vec<u8>> for Capture {
+    fn from(data: Vec<u8>) -> Self {
+        Capture::Bytes(data)
+    }
+}
+
+// A server or client node logs and profiles are collected from
+#[derive(Debug, Clone)]
+enum Target {
+    Server(String),
+    Node(String),
+}
+
+impl Target {
+    fn dir(&self) -> String {
+        match self {
+            Target::Server(name) => format!("server/{}", sanitize(name)),
+            Target::Node(id) => format!("client/{}", sanitize(id)),
+        }
+    }
+
+    fn log_options(&self, log_level: &str) -> LogMonitorOptions {
+        let mut options = LogMonitorOptions {
+            log_level: log_level.to_string(),
+            ..Default::default()
+        };
+        match self {
+            Target::Server(name) => options.server_id = Some(name.clone()),
+            Target::Node(id) => options.node_id = Some(id.clone()),
+        }
+        options
+    }
+
+    fn profile_options(&self) -> ProfileOptions {
+        match self {
+            Target::Server(name) => ProfileOptions {
+                server_id: Some(name.clone()),
+                ..Default::default()
+            },
+            Target::Node(id) => ProfileOptions {
+                node_id: Some(id.clone()),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+//
+// Capture a debug bundle writing the gzipped tarball to `writer`. Individual
+// captures which fail are listed in the manifest rather than aborting the
+// bundle; only failures writing the archive are returned as errors.
+//
+pub async fn capture<W: Write>(
+    client: &NomadClient,
+    options: &DebugOptions,
+    writer: W,
+) -> Result<Manifest> {
+    let started_at = Utc::now();
+    let prefix = format!("nomad-debug-{}", started_at.format("%Y-%m-%d-%H%M%SZ"));
+    let mut bundle = Bundle {
+        tar: tar::Builder::new(GzEncoder::new(writer, Compression::default())),
+        prefix,
+        manifest: Manifest {
+            started_at,
+            finished_at: started_at,
+            address: client.address().to_string(),
+            region: client.region().map(String::from),
+            duration_secs: options.duration.as_secs(),
+            interval_secs: options.interval.as_secs(),
+            servers: Vec::new(),
+            nodes: Vec::new(),
+            files: Vec::new(),
+            errors: Vec::new(),
+        },
+    };
+
+    // Cluster wide state
+    let none = QueryOptions::default();
+    let all = QueryOptions {
+        namespace: Some(ALL_NAMESPACES.to_string()),
+        ..Default::default()
+    };
+    let cluster = [
+        ("cluster/agent-self.json", "/v1/agent/self", &none),
+        ("cluster/leader.json", "/v1/status/leader", &none),
+        ("cluster/peers.json", "/v1/status/peers", &none),
+        ("cluster/regions.json", "/v1/regions", &none),
+        ("cluster/namespaces.json", "/v1/namespaces", &none),
+        ("cluster/jobs.json", "/v1/jobs", &all),
+        ("cluster/allocations.json", "/v1/allocations", &all),
+        ("cluster/evaluations.json", "/v1/evaluations", &all),
+        ("cluster/deployments.json", "/v1/deployments", &all),
+    ];
+    for (path, endpoint, query) in cluster.iter() {
+        bundle.record(path, fetch(client, endpoint, query).await)?;
+    }
+
+    // Expand "all" into the members and nodes of the cluster
+    let members = fetch(client, "/v1/agent/members", &none).await;
+    let servers = expand(&options.servers, &members, |m| names(&m["Members"], "Name"));
+    bundle.record("cluster/members.json", members)?;
+    let nodes = fetch(client, "/v1/nodes", &none).await;
+    let node_ids = expand(&options.nodes, &nodes, |n| names(n, "ID"));
+    bundle.record("cluster/nodes.json", nodes)?;
+
+    bundle.manifest.servers = servers.clone();
+    bundle.manifest.nodes = node_ids.clone();
+    let targets: Vec<Target> = servers
+        .into_iter()
+        .map(Target::Server)
+        .chain(node_ids.into_iter().map(Target::Node))
+        .collect();
+
+    // Logs, cpu profiles and metrics are collected concurrently over the
+    // duration of the capture
+    let deadline = Instant::now() + options.duration;
+    let logs = join_all(
+        targets
+            .iter()
+            .map(|target| collect_logs(client, target, &options.log_level, deadline)),
+    );
+    let cpu_profiles = join_all(targets.iter().filter(|_| options.profiles).map(|target| {
+        let profile_options = ProfileOptions {
+            seconds: Some(options.duration.as_secs().max(1) as u32),
+            ..target.profile_options()
+        };
+        async move { capture_profile(client, Profile::Cpu, &profile_options).await }
+    }));
+    let metrics = collect_metrics(client, options.interval, deadline);
+    let (logs, cpu_profiles, metrics) = tokio::join!(logs, cpu_profiles, metrics);
+
+    for (target, (log, error)) in targets.iter().zip(logs) {
+        let path = format!("{}/monitor.log", target.dir());
+        if !log.is_empty() || error.is_none() {
+            bundle.add(&path, &log)?;
+        }
+        if let Some(e) = error {
+            bundle.record::<Vec<u8>>(&path, Err(e))?;
+        }
+    }
+    let profiled = targets.iter().filter(|_| options.profiles);
+    for (target, profile) in profiled.zip(cpu_profiles) {
+        bundle.record(&format!("{}/profile.prof", target.dir()), profile)?;
+    }
+    for (i, sample) in metrics.into_iter().enumerate() {
+        bundle.record(&format!("metrics/{:04}.json", i), sample)?;
+    }
+
+    // Point in time profiles once the capture is complete
+    if options.profiles {
+        for target in targets.iter() {
+            let heap = capture_profile(client, Profile::Heap, &target.profile_options()).await;
+            bundle.record(&format!("{}/heap.prof", target.dir()), heap)?;
+            let goroutine_options = ProfileOptions {
+                debug: Some(2),
+                ..target.profile_options()
+            };
+            let goroutine = capture_profile(client, Profile::Goroutine, &goroutine_options).await;
+            bundle.record(&format!("{}/goroutine.txt", target.dir()), goroutine)?;
+        }
+    }
+
+    bundle.manifest.finished_at = Utc::now();
+    let manifest = bundle.manifest.clone();
+    bundle.add_json("manifest.json", serde_json::to_value(&manifest)?)?;
+    bundle.tar.into_inner()?.finish()?.flush()?;
+    Ok(manifest)
+}
+
+async fn fetch(
+    client: &NomadClient,
+    path: &str,
+    options: &QueryOptions,
+) -> Result<serde_json::Value> {
+    let (value, _) = client.query(path, options).await?;
+    Ok(value)
+}
+
+//
+// Follow the agent log until the deadline. A monitor error ends the capture
+// early and is returned alongside the lines collected before it.
+//
+async fn collect_logs(
+    client: &NomadClient,
+    target: &Target,
+    log_level: &str,
+    deadline: Instant,
+) -> (Vec<u8>, Option<Error>) {
+    let mut log = Vec::new();
+    let mut stream = match client.agent().monitor(&target.log_options(log_level)).await {
+        Ok(stream) => stream,
+        Err(e) => return (log, Some(e)),
+    };
+    while let Ok(next) = timeout_at(deadline, stream.next()).await {
+        match next {
+            Ok(Some(entry)) => {
+                log.extend_from_slice(entry.raw.as_bytes());
+                log.push(b'\n');
+            }
+            Ok(None) => break,
+            Err(e) => return (log, Some(e)),
+        }
+    }
+    (log, None)
+}
+
+async fn capture_profile(
+    client: &NomadClient,
+    profile: Profile,
+    options: &ProfileOptions,
+) -> Result<Vec<u8>> {
+    let data = client.agent().profile(profile, options).await?;
+    Ok(data.to_vec())
+}
+
+async fn collect_metrics(
+    client: &NomadClient,
+    interval: Duration,
+    deadline: Instant,
+) -> Vec<Result<serde_json::Value>> {
+    let mut samples = Vec::new();
+    let mut next = Instant::now();
+    while next <= deadline {
+        samples.push(client.agent().metrics().await);
+        next += interval.max(Duration::from_secs(1));
+        sleep_until(next.min(deadline)).await;
+    }
+    samples
+}
+
+fn expand<F>(requested: &[String], listed: &Result<serde_json::Value>, names: F) -> Vec<String>
+where
+    F: FnOnce(&serde_json::Value) -> Vec<String>,
+{
+    if !requested.iter().any(|r| r == DEBUG_TARGET_ALL) {
+        return requested.to_vec();
+    }
+    listed.as_ref().map(names).unwrap_or_default()
+}
+
+fn names(list: &serde_json::Value, key: &str) -> Vec<String> {
+    list.as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item[key].as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+//
+// Replace string values of keys that look like they hold credentials, such
+// as the ACL replication token or Vault token in the agent configuration
+//
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if REDACTED_KEYS.iter().any(|k| key.contains(k)) {
+                    if let serde_json::Value::String(s) = value {
+                        if !s.is_empty() {
+                            *s = REDACTED.to_string();
+                        }
+                        continue;
+                    }
+                }
+                redact(value);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+    use std::io::Read;
+
+    #[test]
+    fn redacts_credentials() {
+        let mut config = json!({
+            "ACL": {"ReplicationToken": "s3cr3t", "Enabled": true},
+            "Vault": [{"Token": "hvs.abc", "Addr": "https://vault:8200"}],
+            "Server": {"EncryptKey": "", "Enabled": true}
+        });
+        redact(&mut config);
+        assert_eq!(config["ACL"]["ReplicationToken"], REDACTED);
+        assert_eq!(config["Vault"][0]["Token"], REDACTED);
+        assert_eq!(config["Vault"][0]["Addr"], "https://vault:8200");
+        assert_eq!(config["Server"]["EncryptKey"], "");
+    }
+
+    #[tokio::test]
+    async fn capture_bundle() {
+        let address = serve(|request| match request.path.as_str() {
+            "/v1/agent/self" => StubResponse::json(&json!({
+                "config": {"ACL": {"ReplicationToken": "s3cr3t"}},
+                "member": {"Name": "server-1.global"}
+            })),
+            "/v1/agent/members" => StubResponse::json(&json!({
+                "Members": [{"Name": "server-1.global"}, {"Name": "server-2.global"}]
+            })),
+            "/v1/agent/monitor" => {
+                let server = &request.query["server_id"];
+                let data = base64::encode(format!("[INFO]  agent: {}\n", server));
+                let mut body = format!("{{\"Data\":\"{}\"}}\n", data);
+                if server == "server-1.global" {
+                    body.push_str("{\"Data\":]\n");
+                }
+                StubResponse::raw(200, body.as_bytes())
+            }
+            "/v1/metrics" => StubResponse::json(&json!({"Gauges": []})),
+            "/v1/status/leader" => StubResponse::json(&json!("10.0.0.1:4647")),
+            "/v1/jobs" | "/v1/nodes" | "/v1/allocations" | "/v1/evaluations"
+            | "/v1/deployments" => {
+                assert!(
+                    request.path == "/v1/nodes" || request.query["namespace"] == ALL_NAMESPACES
+                );
+                StubResponse::json(&json!([]))
+            }
+            _ => StubResponse::raw(404, b"not found"),
+        })
+        .await;
+
+        let client = NomadClient::new(&address).with_token("management");
+        let options = DebugOptions {
+            duration: Duration::from_millis(300),
+            interval: Duration::from_secs(1),
+            servers: vec![DEBUG_TARGET_ALL.to_string()],
+            profiles: false,
+            ..Default::default()
+        };
+        let mut archive = Vec::new();
+        let manifest = capture(&client, &options, &mut archive)
+            .await
+            .expect("capture");
+        assert_eq!(manifest.servers, vec!["server-1.global", "server-2.global"]);
+        assert!(manifest
+            .errors
+            .iter()
+            .any(|e| e.path == "cluster/peers.json"));
+        assert!(manifest
+            .errors
+            .iter()
+            .any(|e| e.path == "server/server-1.global/monitor.log"));
+
+        let mut contents = std::collections::HashMap::new();
+        let mut tar = tar::Archive::new(GzDecoder::new(&archive[..]));
+        for entry in tar.entries().expect("entries") {
+            let mut entry = entry.expect("entry");
+            let path = entry.path().expect("path").to_string_lossy().to_string();
+            let mut text = String::new();
+            entry.read_to_string(&mut text).expect("read");
+            let (_, path) = path.split_once('/').expect("prefix");
+            contents.insert(path.to_string(), text);
+        }
+        assert!(contents["cluster/agent-self.json"].contains(REDACTED));
+        assert!(!contents.values().any(|text| text.contains("s3cr3t")));
+        assert!(!contents.values().any(|text| text.contains("management")));
+        assert!(contents["server/server-1.global/monitor.log"].contains("server-1.global"));
+        assert!(contents["server/server-2.global/monitor.log"].contains("server-2.global"));
+        assert!(contents.contains_key("metrics/0000.json"));
+        assert!(contents.contains_key("manifest.json"));
+    }
+}
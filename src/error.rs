@@ -0,0 +1,123 @@
+use std::fmt;
+
+use crate::model::validation::ValidationError;
+use crate::model::variables::Variable;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    // Transport level failure talking to the agent
+    Http(reqwest::Error),
+    // Response body could not be (de)serialized
+    Json(serde_json::Error),
+    // Reading or writing a local file failed
+    Io(std::io::Error),
+    // HCL text could not be parsed
+    Hcl(hcl::Error),
+    // The agent answered with a non-success status code
+    Api {
+        status: u16,
+        message: String,
+    },
+    // The agent answered with success but the body lacked what was asked for
+    UnexpectedResponse(String),
+    // No object matched a prefix
+    NotFound(String),
+    // A prefix matched more than one object. When `truncated` the server
+    // returned only some of the matches.
+    Ambiguous {
+        prefix: String,
+        matches: Vec<String>,
+        truncated: bool,
+    },
+    // A check-and-set write lost to a concurrent update, holds the copy
+    // currently stored by the server
+    Conflict(Box<Variable>),
+    // Downloaded data did not match the checksum sent by the agent
+    Integrity(String),
+    // A snapshot archive could not be read
+    Snapshot(String),
+    // Gave up waiting on a long running operation
+    Timeout(String),
+    // The request was rejected locally before being sent
+    InvalidRequest(String),
+    // A job spec failed local validation, see `Job::validate`
+    Validation(Vec<ValidationError>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "http error: {}", e),
+            Error::Json(e) => write!(f, "json error: {}", e),
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Hcl(e) => write!(f, "hcl error: {}", e),
+            Error::Api { status, message } => {
+                write!(f, "unexpected response code {}: {}", status, message)
+            }
+            Error::UnexpectedResponse(reason) => write!(f, "unexpected response: {}", reason),
+            Error::NotFound(what) => write!(f, "not found: {}", what),
+            Error::Ambiguous {
+                prefix,
+                matches,
+                truncated,
+            } => write!(
+                f,
+                "prefix \"{}\" matched multiple ids: {}{}",
+                prefix,
+                matches.join(", "),
+                if *truncated { ", ..." } else { "" }
+            ),
+            Error::Conflict(current) => write!(
+                f,
+                "check-and-set conflict on \"{}\", current index is {}",
+                current.path, current.modify_index
+            ),
+            Error::Integrity(reason) => write!(f, "integrity check failed: {}", reason),
+            Error::Snapshot(reason) => write!(f, "snapshot error: {}", reason),
+            Error::Timeout(what) => write!(f, "timed out: {}", what),
+            Error::InvalidRequest(reason) => write!(f, "invalid request: {}", reason),
+            Error::Validation(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "invalid job: {}", errors.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Hcl(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<hcl::Error> for Error {
+    fn from(e: hcl::Error) -> Self {
+        Error::Hcl(e)
+    }
+}
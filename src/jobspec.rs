@@ -0,0 +1,865 @@
+//
+// Render jobs, task groups and tasks as HCL job specifications, for review
+// diffs and generating templates. The JSON form of each type is walked with
+// the rules in `FIELDS` mapping API field names onto jobspec blocks and
+// attributes.
+//
+use hcl::expr::{Heredoc, TemplateExpr};
+use hcl::format::{Format, Formatter};
+use hcl::{Attribute, Block, Body, Expression, Identifier, Object, ObjectKey};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::model::jobs::Job;
+use crate::model::serde_helpers::readable_duration;
+use crate::model::tasks::{Task, TaskGroup};
+
+// Field describes how a field of the JSON form is written in HCL
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field<'a> {
+    Skip,
+    // An attribute under a different name
+    Attr(&'a str),
+    // An attribute which is left out when zero
+    NonZero(&'a str),
+    // An object written as a block
+    Block(&'a str),
+    // A list of objects written as repeated blocks
+    Blocks(&'a str),
+    // A list of objects written as blocks labelled by one of their fields
+    Labelled(&'a str, &'a str),
+    // A map of objects written as blocks labelled by their key
+    Keyed(&'a str),
+    // A map written with its keys as is, such as meta, env and driver config
+    Raw(&'a str),
+}
+
+// Rules for fields by enclosing block, "*" matching any block. Fields without
+// a rule become snake case attributes, or blocks if they hold objects.
+const FIELDS: &[(&str, &str, Field<'static>)] = &[
+    // Server managed fields of a job
+    ("job", "Stop", Field::Skip),
+    ("job", "ParentID", Field::Skip),
+    ("job", "Dispatched", Field::Skip),
+    ("job", "Payload", Field::Skip),
+    ("job", "Status", Field::Skip),
+    ("job", "StatusDescription", Field::Skip),
+    ("job", "Stable", Field::Skip),
+    ("job", "Version", Field::Skip),
+    ("job", "SubmitTime", Field::Skip),
+    ("job", "JobModifyIndex", Field::Skip),
+    ("job", "NomadTokenID", Field::Skip),
+    ("job", "ConsulToken", Field::Skip),
+    ("job", "VaultToken", Field::Skip),
+    ("job", "VaultNamespace", Field::Skip),
+    ("job", "ParameterizedJob", Field::Block("parameterized")),
+    ("job", "Multiregion", Field::Block("multiregion")),
+    ("*", "ID", Field::Skip),
+    ("*", "Id", Field::Skip),
+    ("*", "CreateIndex", Field::Skip),
+    ("*", "ModifyIndex", Field::Skip),
+    ("*", "ExtraKeyHcl", Field::Skip),
+    ("*", "TaskGroups", Field::Labelled("group", "Name")),
+    ("*", "Tasks", Field::Labelled("task", "Name")),
+    ("*", "Constraints", Field::Blocks("constraint")),
+    ("*", "Affinities", Field::Blocks("affinity")),
+    ("*", "Spreads", Field::Blocks("spread")),
+    ("*", "Services", Field::Blocks("service")),
+    ("*", "Checks", Field::Blocks("check")),
+    ("*", "Networks", Field::Blocks("network")),
+    ("*", "Devices", Field::Labelled("device", "Name")),
+    ("*", "LTarget", Field::Attr("attribute")),
+    ("*", "RTarget", Field::Attr("value")),
+    ("*", "Operand", Field::Attr("operator")),
+    ("*", "LogConfig", Field::Block("logs")),
+    ("*", "RestartPolicy", Field::Block("restart")),
+    ("*", "ReschedulePolicy", Field::Block("reschedule")),
+    ("*", "CheckRestart", Field::Block("check_restart")),
+    ("*", "PortLabel", Field::Attr("port")),
+    ("*", "Meta", Field::Raw("meta")),
+    ("*", "CanaryMeta", Field::Raw("canary_meta")),
+    ("*", "Env", Field::Raw("env")),
+    ("*", "Config", Field::Raw("config")),
+    ("multiregion", "Strategy", Field::Blocks("strategy")),
+    ("multiregion", "Regions", Field::Labelled("region", "Name")),
+    ("periodic", "Cron", Field::Attr("cron")),
+    ("periodic", "SpecType", Field::Skip),
+    ("group", "Volumes", Field::Keyed("volume")),
+    ("group", "Scaling", Field::Block("scaling")),
+    ("volume", "MountOptions", Field::Blocks("mount_options")),
+    ("ephemeral_disk", "SizeMB", Field::Attr("size")),
+    ("scaling", "Target", Field::Skip),
+    ("scaling", "Namespace", Field::Skip),
+    ("scaling", "Policy", Field::Raw("policy")),
+    ("task", "Kind", Field::Skip),
+    ("task", "ScalingPolicy", Field::Skip),
+    ("task", "Templates", Field::Blocks("template")),
+    ("task", "Artifacts", Field::Blocks("artifact")),
+    ("task", "VolumeMounts", Field::Blocks("volume_mount")),
+    (
+        "task",
+        "DispatchPayloadConfig",
+        Field::Block("dispatch_payload"),
+    ),
+    ("task", "CsiPluginConfig", Field::Block("csi_plugin")),
+    ("csi_plugin", "Id", Field::Attr("id")),
+    ("resources", "CPU", Field::Attr("cpu")),
+    ("resources", "MemoryMB", Field::Attr("memory")),
+    ("resources", "DiskMB", Field::Skip),
+    ("network", "IP", Field::Skip),
+    ("network", "CIDR", Field::Skip),
+    ("network", "Device", Field::Skip),
+    ("network", "DNS", Field::Block("dns")),
+    ("network", "ReservedPorts", Field::Labelled("port", "Label")),
+    ("network", "DynamicPorts", Field::Labelled("port", "Label")),
+    ("port", "Value", Field::NonZero("static")),
+    ("port", "To", Field::NonZero("to")),
+    ("logs", "MaxFileSizeMB", Field::Attr("max_file_size")),
+    ("template", "EmbeddedTmpl", Field::Attr("data")),
+    ("template", "SourcePath", Field::Attr("source")),
+    ("template", "DestPath", Field::Attr("destination")),
+    ("template", "LeftDelim", Field::Attr("left_delimiter")),
+    ("template", "RightDelim", Field::Attr("right_delimiter")),
+    ("template", "Envvars", Field::Attr("env")),
+    ("artifact", "GetterSource", Field::Attr("source")),
+    ("artifact", "RelativeDest", Field::Attr("destination")),
+    ("artifact", "GetterMode", Field::Attr("mode")),
+    ("artifact", "GetterOptions", Field::Raw("options")),
+    ("artifact", "GetterHeaders", Field::Raw("headers")),
+    ("vault", "Env", Field::Attr("env")),
+    ("check", "Header", Field::Raw("header")),
+    ("spread", "SpreadTarget", Field::Labelled("target", "Label")),
+    ("proxy", "Upstreams", Field::Blocks("upstreams")),
+    ("proxy", "ExposeConfig", Field::Block("expose")),
+    (
+        "proxy",
+        "EnvoyGatewayBindAddresses",
+        Field::Keyed("envoy_gateway_bind_addresses"),
+    ),
+    ("expose", "Path", Field::Blocks("path")),
+    ("ingress", "Listeners", Field::Blocks("listener")),
+];
+
+// Flags which default to true so must be written when false
+const KEEP_FALSE: &[&str] = &["Enabled", "Unlimited"];
+
+// Fields holding durations, sent as nanoseconds and written as "30s"
+const DURATIONS: &[&str] = &[
+    "ConnectTimeout",
+    "Delay",
+    "Grace",
+    "HealthyDeadline",
+    "Interval",
+    "KillTimeout",
+    "MaxDelay",
+    "MinHealthyTime",
+    "ProgressDeadline",
+    "ShutdownDelay",
+    "Splay",
+    "Stagger",
+    "StopAfterClientDisconnect",
+    "Timeout",
+    "VaultGrace",
+];
+
+// ToHcl renders a job specification, or part of one, as HCL
+pub trait ToHcl {
+    fn to_hcl(&self) -> Result<String>;
+}
+
+impl ToHcl for Job {
+    fn to_hcl(&self) -> Result<String> {
+        let mut fields = to_object(self)?;
+        let id = take_string(&mut fields, "ID");
+        if fields.get("Name").and_then(Value::as_str) == Some(id.as_str()) {
+            fields.remove("Name");
+        }
+        render(block("job", Some(&id), &fields)?)
+    }
+}
+
+impl ToHcl for TaskGroup {
+    fn to_hcl(&self) -> Result<String> {
+        let mut fields = to_object(self)?;
+        let name = take_string(&mut fields, "Name");
+        render(block("group", Some(&name), &fields)?)
+    }
+}
+
+impl ToHcl for Task {
+    fn to_hcl(&self) -> Result<String> {
+        let mut fields = to_object(self)?;
+        let name = take_string(&mut fields, "Name");
+        render(block("task", Some(&name), &fields)?)
+    }
+}
+
+fn to_object<T: Serialize>(value: &T) -> Result<Map<String, Value>> {
+    match serde_json::to_value(value)? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(Error::InvalidRequest("expected an object".to_string())),
+    }
+}
+
+fn take_string(fields: &mut Map<String, Value>, key: &str) -> String {
+    match fields.remove(key) {
+        Some(Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn render(block: Block) -> Result<String> {
+    let mut formatter = Formatter::builder().compact_arrays(true).build_vec();
+    Ok(Body::builder()
+        .add_block(block)
+        .build()
+        .format_string(&mut formatter)?)
+}
+
+//
+// Strings are written the way they would be by hand: interpolations such as
+// "${attr.kernel.name}" are left for Nomad to resolve rather than escaped,
+// and multi-line strings like template data become heredocs
+//
+fn expression(value: &Value) -> Result<Expression> {
+    let plain = |s: &str| !s.contains(|c: char| c == '"' || c == '\\' || c.is_control());
+    match value {
+        Value::String(s) if s.contains("${") && plain(s) => Ok(Expression::TemplateExpr(Box::new(
+            TemplateExpr::QuotedString(s.clone()),
+        ))),
+        Value::String(s)
+            if s.ends_with('\n')
+                && s.trim_end().contains('\n')
+                && !s.contains("${")
+                && !s.contains("%{")
+                && !s.lines().any(|line| line == "EOT") =>
+        {
+            let heredoc = Heredoc::new(Identifier::unchecked("EOT"), s.clone());
+            Ok(Expression::TemplateExpr(Box::new(TemplateExpr::Heredoc(
+                heredoc,
+            ))))
+        }
+        Value::Array(items) => Ok(Expression::Array(
+            items.iter().map(expression).collect::<Result<_>>()?,
+        )),
+        Value::Object(fields) => {
+            let mut object = Object::new();
+            for (key, value) in fields.iter() {
+                let key = match Identifier::new(key.as_str()) {
+                    Ok(ident) => ObjectKey::Identifier(ident),
+                    Err(_) => ObjectKey::Expression(key.as_str().into()),
+                };
+                object.insert(key, expression(value)?);
+            }
+            Ok(Expression::Object(object))
+        }
+        other => Ok(hcl::to_expression(other)?),
+    }
+}
+
+// The rule for a field, `None` if it has no explicit rule
+fn field(parent: &str, key: &str) -> Option<Field<'static>> {
+    FIELDS
+        .iter()
+        .find(|(p, k, _)| *p == parent && *k == key)
+        .or_else(|| FIELDS.iter().find(|(p, k, _)| *p == "*" && *k == key))
+        .map(|(_, _, rule)| *rule)
+}
+
+fn is_empty(key: &str, value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(b) => !b && !KEEP_FALSE.contains(&key),
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        Value::Number(_) => false,
+    }
+}
+
+fn block(ident: &str, label: Option<&str>, fields: &Map<String, Value>) -> Result<Block> {
+    let mut attributes = Vec::new();
+    let mut blocks = Vec::new();
+    for (key, value) in fields.iter() {
+        if is_empty(key, value) {
+            continue;
+        }
+        let duration;
+        let value = match value.as_u64() {
+            Some(nanos) if DURATIONS.contains(&key.as_str()) => {
+                duration = Value::from(readable_duration::format(&Duration::from_nanos(nanos)));
+                &duration
+            }
+            _ => value,
+        };
+        let name = snake_case(key);
+        let rule = field(ident, key).unwrap_or(match value {
+            Value::Object(_) => Field::Block(&name),
+            Value::Array(items) if items.iter().all(Value::is_object) => Field::Blocks(&name),
+            _ => Field::Attr(&name),
+        });
+        match rule {
+            Field::Skip => {}
+            Field::Attr(name) => attributes.push(Attribute::new(name, expression(value)?)),
+            Field::NonZero(name) => {
+                if value.as_i64() != Some(0) {
+                    attributes.push(Attribute::new(name, expression(value)?));
+                }
+            }
+            Field::Block(name) => {
+                if let Value::Object(fields) = value {
+                    blocks.push(block(name, None, fields)?);
+                }
+            }
+            Field::Blocks(name) => {
+                for item in value.as_array().into_iter().flatten() {
+                    if let Value::Object(fields) = item {
+                        blocks.push(block(name, None, fields)?);
+                    }
+                }
+            }
+            Field::Labelled(name, label_key) => {
+                for item in value.as_array().into_iter().flatten() {
+                    if let Value::Object(fields) = item {
+                        let mut fields = fields.clone();
+                        let label = take_string(&mut fields, label_key);
+                        blocks.push(block(name, Some(&label), &fields)?);
+                    }
+                }
+            }
+            Field::Keyed(name) => {
+                for (label, item) in value.as_object().into_iter().flatten() {
+                    if let Value::Object(fields) = item {
+                        let mut fields = fields.clone();
+                        fields.remove("Name");
+                        blocks.push(block(name, Some(label), &fields)?);
+                    }
+                }
+            }
+            Field::Raw(name) => match value {
+                // Keys such as "com.example.owner" can only be written as an
+                // object attribute
+                Value::Object(fields)
+                    if fields.keys().all(|k| Identifier::new(k.as_str()).is_ok()) =>
+                {
+                    blocks.push(raw_block(name, None, fields)?)
+                }
+                _ => attributes.push(Attribute::new(name, expression(value)?)),
+            },
+        }
+    }
+
+    let mut builder = Block::builder(ident);
+    if let Some(label) = label {
+        builder = builder.add_label(label);
+    }
+    Ok(builder
+        .add_attributes(attributes)
+        .add_blocks(blocks)
+        .build())
+}
+
+//
+// Driver config and the like have no schema here; nested objects are written
+// as blocks and lists of objects as repeated blocks, matching drivers such as
+// docker
+//
+fn raw_block(ident: &str, label: Option<&str>, fields: &Map<String, Value>) -> Result<Block> {
+    let mut attributes = Vec::new();
+    let mut blocks = Vec::new();
+    for (key, value) in fields.iter() {
+        let nested = match value {
+            Value::Object(fields) if fields.keys().all(|k| Identifier::new(k.as_str()).is_ok()) => {
+                vec![fields]
+            }
+            Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+                items.iter().filter_map(Value::as_object).collect()
+            }
+            _ => Vec::new(),
+        };
+        if nested.is_empty() {
+            attributes.push(Attribute::new(key.as_str(), expression(value)?));
+        } else {
+            for fields in nested {
+                blocks.push(raw_block(key, None, fields)?);
+            }
+        }
+    }
+
+    let mut builder = Block::builder(ident);
+    if let Some(label) = label {
+        builder = builder.add_label(label);
+    }
+    Ok(builder
+        .add_attributes(attributes)
+        .add_blocks(blocks)
+        .build())
+}
+
+// "MaxParallel" to "max_parallel", keeping acronyms together as in "GRPCUseTLS"
+fn snake_case(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut out = String::with_capacity(key.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use crate::client::NomadClient;
+    use std::collections::HashMap;
+
+    const JOBSPEC: &str = r#"
+job "cache" {
+  datacenters = ["dc1", "dc2"]
+  type = "service"
+  priority = 60
+
+  meta {
+    team = "platform"
+  }
+
+  constraint {
+    attribute = "${attr.kernel.name}"
+    value = "linux"
+  }
+
+  update {
+    max_parallel = 1
+    auto_revert = true
+  }
+
+  group "cache" {
+    count = 3
+
+    network {
+      mode = "bridge"
+
+      port "db" {
+        to = 6379
+      }
+
+      port "metrics" {
+        static = 9121
+      }
+    }
+
+    volume "data" {
+      type = "host"
+      source = "redis-data"
+    }
+
+    service {
+      name = "redis"
+      port = "db"
+      tags = ["cache", "v7"]
+
+      check {
+        type = "tcp"
+        interval = "10s"
+        timeout = "2s"
+      }
+    }
+
+    restart {
+      attempts = 2
+      interval = "30m"
+      delay = "15s"
+      mode = "fail"
+    }
+
+    task "redis" {
+      driver = "docker"
+
+      config {
+        image = "redis:7"
+        ports = ["db"]
+        args = ["--appendonly", "yes", "--dir", "${NOMAD_ALLOC_DIR}"]
+
+        mount {
+          type = "bind"
+          source = "local/redis.conf"
+          target = "/etc/redis.conf"
+        }
+
+        mount {
+          type = "tmpfs"
+          target = "/scratch"
+        }
+      }
+
+      env {
+        REDIS_PORT = "6379"
+      }
+
+      template {
+        data = <<EOT
+maxmemory {{ env "NOMAD_MEMORY_LIMIT" }}mb
+dir /data
+EOT
+        destination = "local/redis.conf"
+        change_mode = "restart"
+      }
+
+      volume_mount {
+        volume = "data"
+        destination = "/data"
+      }
+
+      resources {
+        cpu = 500
+        memory = 256
+      }
+    }
+  }
+}
+"#;
+
+    // The agent's response to parsing `JOBSPEC` with canonicalize set, as
+    // returned by POST /v1/jobs/parse
+    const PARSED: &str = r#"{
+        "Region": "global",
+        "Namespace": "default",
+        "ID": "cache",
+        "Name": "cache",
+        "Type": "service",
+        "Priority": 60,
+        "AllAtOnce": false,
+        "Datacenters": ["dc1", "dc2"],
+        "Constraints": [{
+            "LTarget": "${attr.kernel.name}",
+            "RTarget": "linux",
+            "Operand": "="
+        }],
+        "Affinities": null,
+        "TaskGroups": [{
+            "Name": "cache",
+            "Count": 3,
+            "Constraints": null,
+            "Affinities": null,
+            "Tasks": [{
+                "Name": "redis",
+                "Driver": "docker",
+                "User": "",
+                "Lifecycle": null,
+                "Config": {
+                    "args": ["--appendonly", "yes", "--dir", "${NOMAD_ALLOC_DIR}"],
+                    "image": "redis:7",
+                    "mount": [
+                        {
+                            "source": "local/redis.conf",
+                            "target": "/etc/redis.conf",
+                            "type": "bind"
+                        },
+                        {"target": "/scratch", "type": "tmpfs"}
+                    ],
+                    "ports": ["db"]
+                },
+                "Constraints": null,
+                "Affinities": null,
+                "Env": {"REDIS_PORT": "6379"},
+                "Services": null,
+                "Resources": {
+                    "CPU": 500,
+                    "Cores": 0,
+                    "MemoryMB": 256,
+                    "MemoryMaxMB": 0,
+                    "DiskMB": null,
+                    "Networks": null,
+                    "Devices": null,
+                    "IOPS": null
+                },
+                "RestartPolicy": {
+                    "Interval": 1800000000000,
+                    "Attempts": 2,
+                    "Delay": 15000000000,
+                    "Mode": "fail"
+                },
+                "Meta": null,
+                "KillTimeout": 5000000000,
+                "LogConfig": {"MaxFiles": 10, "MaxFileSizeMB": 10},
+                "Artifacts": null,
+                "Vault": null,
+                "Templates": [{
+                    "SourcePath": "",
+                    "DestPath": "local/redis.conf",
+                    "EmbeddedTmpl": "maxmemory {{ env \"NOMAD_MEMORY_LIMIT\" }}mb\ndir /data\n",
+                    "ChangeMode": "restart",
+                    "ChangeSignal": "",
+                    "Splay": 5000000000,
+                    "Perms": "0644",
+                    "LeftDelim": "{{",
+                    "RightDelim": "}}",
+                    "Envvars": false,
+                    "VaultGrace": 0,
+                    "Wait": null
+                }],
+                "DispatchPayload": null,
+                "VolumeMounts": [{
+                    "Volume": "data",
+                    "Destination": "/data",
+                    "ReadOnly": false,
+                    "PropagationMode": "private"
+                }],
+                "CSIPluginConfig": null,
+                "Leader": false,
+                "ShutdownDelay": 0,
+                "KillSignal": "",
+                "Kind": "",
+                "ScalingPolicies": null
+            }],
+            "Spreads": null,
+            "Volumes": {
+                "data": {
+                    "Name": "data",
+                    "Type": "host",
+                    "Source": "redis-data",
+                    "ReadOnly": false,
+                    "AccessMode": "",
+                    "AttachmentMode": "",
+                    "MountOptions": null,
+                    "PerAlloc": false
+                }
+            },
+            "RestartPolicy": {
+                "Interval": 1800000000000,
+                "Attempts": 2,
+                "Delay": 15000000000,
+                "Mode": "fail"
+            },
+            "ReschedulePolicy": {
+                "Attempts": 0,
+                "Interval": 0,
+                "Delay": 30000000000,
+                "DelayFunction": "exponential",
+                "MaxDelay": 3600000000000,
+                "Unlimited": true
+            },
+            "EphemeralDisk": {"Sticky": false, "Migrate": false, "SizeMB": 300},
+            "Update": {
+                "Stagger": 30000000000,
+                "MaxParallel": 1,
+                "HealthCheck": "checks",
+                "MinHealthyTime": 10000000000,
+                "HealthyDeadline": 300000000000,
+                "ProgressDeadline": 600000000000,
+                "Canary": 0,
+                "AutoRevert": true,
+                "AutoPromote": false
+            },
+            "Migrate": {
+                "MaxParallel": 1,
+                "HealthCheck": "checks",
+                "MinHealthyTime": 10000000000,
+                "HealthyDeadline": 300000000000
+            },
+            "Networks": [{
+                "Mode": "bridge",
+                "Device": "",
+                "CIDR": "",
+                "IP": "",
+                "DNS": null,
+                "ReservedPorts": [{
+                    "Label": "metrics",
+                    "Value": 9121,
+                    "To": 0,
+                    "HostNetwork": "default"
+                }],
+                "DynamicPorts": [{
+                    "Label": "db",
+                    "Value": 0,
+                    "To": 6379,
+                    "HostNetwork": "default"
+                }],
+                "MBits": 0
+            }],
+            "Meta": null,
+            "Services": [{
+                "Name": "redis",
+                "Tags": ["cache", "v7"],
+                "CanaryTags": null,
+                "EnableTagOverride": false,
+                "PortLabel": "db",
+                "AddressMode": "auto",
+                "Address": "",
+                "Checks": [{
+                    "Name": "",
+                    "Type": "tcp",
+                    "Command": "",
+                    "Args": null,
+                    "Path": "",
+                    "Protocol": "",
+                    "PortLabel": "",
+                    "Expose": false,
+                    "AddressMode": "",
+                    "Interval": 10000000000,
+                    "Timeout": 2000000000,
+                    "InitialStatus": "",
+                    "TLSSkipVerify": false,
+                    "Header": null,
+                    "Method": "",
+                    "CheckRestart": null,
+                    "GRPCService": "",
+                    "GRPCUseTLS": false,
+                    "TaskName": "",
+                    "SuccessBeforePassing": 0,
+                    "FailuresBeforeCritical": 0,
+                    "Body": "",
+                    "OnUpdate": "require_healthy"
+                }],
+                "CheckRestart": null,
+                "Connect": null,
+                "Meta": null,
+                "CanaryMeta": null,
+                "TaggedAddresses": null,
+                "TaskName": "",
+                "OnUpdate": "require_healthy",
+                "Provider": "consul"
+            }],
+            "ShutdownDelay": null,
+            "StopAfterClientDisconnect": null,
+            "MaxClientDisconnect": null,
+            "Scaling": null
+        }],
+        "Update": {
+            "Stagger": 30000000000,
+            "MaxParallel": 1,
+            "HealthCheck": "checks",
+            "MinHealthyTime": 10000000000,
+            "HealthyDeadline": 300000000000,
+            "ProgressDeadline": 600000000000,
+            "Canary": 0,
+            "AutoRevert": true,
+            "AutoPromote": false
+        },
+        "Multiregion": null,
+        "Spreads": null,
+        "Periodic": null,
+        "ParameterizedJob": null,
+        "Reschedule": null,
+        "Migrate": null,
+        "Meta": {"team": "platform"},
+        "ConsulToken": "",
+        "VaultToken": "",
+        "Stop": false,
+        "ParentID": "",
+        "Dispatched": false,
+        "Payload": null,
+        "ConsulNamespace": "",
+        "VaultNamespace": "",
+        "NomadTokenID": "",
+        "Status": "",
+        "StatusDescription": "",
+        "Stable": false,
+        "Version": 0,
+        "SubmitTime": null,
+        "CreateIndex": 0,
+        "ModifyIndex": 0,
+        "JobModifyIndex": 0
+    }"#;
+
+    #[tokio::test]
+    async fn parse_and_emit() {
+        let address = serve(|request| {
+            assert_eq!(request.path, "/v1/jobs/parse");
+            let body: Value = serde_json::from_slice(&request.body).expect("parse body");
+            assert_eq!(body["Canonicalize"], Value::Bool(true));
+            if body["JobHCL"] == JOBSPEC {
+                assert_eq!(body["Variables"], "image = \"redis:7\"\n");
+            } else {
+                // The emitted spec sent back, the agent parses it to the
+                // same job
+                let text = body["JobHCL"].as_str().expect("emitted spec");
+                hcl::parse(text).expect("emitted hcl");
+                assert!(text.starts_with("job \"cache\" {\n"));
+            }
+            StubResponse::raw(200, PARSED.as_bytes())
+        })
+        .await;
+
+        let client = NomadClient::new(&address);
+        let mut variables = HashMap::new();
+        variables.insert("image".to_string(), "redis:7".to_string());
+
+        let job = client
+            .jobs()
+            .parse_hcl(JOBSPEC, true, &variables)
+            .await
+            .expect("parse");
+        assert_eq!(job.id.as_deref(), Some("cache"));
+        let group = &job.task_groups[0];
+        assert_eq!(group.count, Some(3));
+        let network = &group.networks[0];
+        assert_eq!(network.dynamic_ports.len(), 1);
+        assert_eq!(network.dynamic_ports[0].label.as_deref(), Some("db"));
+        assert_eq!(network.dynamic_ports[0].to, Some(6379));
+        assert_eq!(network.reserved_ports.len(), 1);
+        assert_eq!(network.reserved_ports[0].value, Some(9121));
+        assert_eq!(
+            group.tasks[0].resources.as_ref().unwrap().memory_mb,
+            Some(256)
+        );
+
+        // The emitted spec is valid HCL with durations written as strings
+        let text = job.to_hcl().expect("emit");
+        hcl::parse(&text).expect("emitted hcl");
+        assert!(text.starts_with("job \"cache\" {\n"));
+        assert!(text.contains(
+            "      port \"db\" {\n        host_network = \"default\"\n        to = 6379\n      }\n"
+        ));
+        assert!(text.contains(
+            "      port \"metrics\" {\n        host_network = \"default\"\n        static = 9121\n      }\n"
+        ));
+        assert!(text.contains(
+            "    restart {\n      attempts = 2\n      delay = \"15s\"\n      interval = \"30m0s\"\n"
+        ));
+        assert!(text.contains("        interval = \"10s\"\n"));
+        assert!(text.contains("        timeout = \"2s\"\n"));
+
+        // Parsing the emitted spec gives back the same job, which emits the
+        // same spec
+        let reparsed = client
+            .jobs()
+            .parse_hcl(&text, true, &HashMap::new())
+            .await
+            .expect("reparse");
+        assert_eq!(
+            serde_json::to_value(&reparsed).expect("reparsed"),
+            serde_json::to_value(&job).expect("job")
+        );
+        assert_eq!(reparsed.to_hcl().expect("emit reparsed"), text);
+
+        let task = group.tasks[0].to_hcl().expect("emit task");
+        assert!(task.starts_with("task \"redis\" {\n  driver = \"docker\"\n"));
+        assert!(task.contains("  kill_timeout = \"5s\"\n"));
+        assert!(task.contains("    mount {\n"));
+        assert!(task.contains("    data = <<EOT\nmaxmemory"));
+        assert!(task.contains("\"${NOMAD_ALLOC_DIR}\"]"));
+        assert!(task.contains("  resources {\n    cpu = 500\n    memory = 256\n  }\n"));
+    }
+
+    #[test]
+    fn snake_case_keys() {
+        assert_eq!(snake_case("MaxParallel"), "max_parallel");
+        assert_eq!(snake_case("GRPCUseTLS"), "grpc_use_tls");
+        assert_eq!(
+            snake_case("EnvoyDNSDiscoveryType"),
+            "envoy_dns_discovery_type"
+        );
+        assert_eq!(snake_case("CAFile"), "ca_file");
+    }
+}
@@ -1,17 +1,35 @@
 pub mod chunked_response;
+pub mod client;
+pub mod debug;
+pub mod error;
+pub mod jobspec;
+pub mod resolver;
+pub mod snapshot;
 
 pub mod model {
+    pub mod acl;
+    pub mod agent;
     pub mod allocations;
+    pub mod builder;
+    pub mod canonicalize;
     pub mod constraint;
     pub mod csi;
     pub mod deployments;
+    pub mod diff;
+    pub mod drivers;
     pub mod evaluations;
     pub mod event_stream;
     pub mod jobs;
+    pub mod namespaces;
     pub mod nodes;
+    pub mod operator;
     pub mod resources;
     pub mod scaling;
+    pub mod search;
+    pub mod secret;
     pub mod serde_helpers;
     pub mod services;
     pub mod tasks;
+    pub mod validation;
+    pub mod variables;
 }
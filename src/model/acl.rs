@@ -0,0 +1,349 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::secret::Secret;
+use super::serde_helpers::nanos_duration;
+
+pub const ACL_TOKEN_TYPE_CLIENT: &str = "client";
+pub const ACL_TOKEN_TYPE_MANAGEMENT: &str = "management";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLTokenRoleLink {
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+// ACLToken is a token used to authenticate requests
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLToken {
+    #[serde(rename = "AccessorID", skip_serializing_if = "String::is_empty")]
+    pub accessor_id: String,
+    #[serde(rename = "SecretID", skip_serializing_if = "String::is_empty")]
+    pub secret_id: Secret<String>,
+    pub name: String,
+    #[serde(rename = "Type")]
+    pub token_type: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub policies: Vec<String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub roles: Vec<ACLTokenRoleLink>,
+    pub global: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<DateTime<Utc>>,
+    #[serde(
+        rename = "ExpirationTTL",
+        with = "nanos_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expiration_ttl: Option<Duration>,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+// ACLTokenListStub is returned when listing tokens and has no secret
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLTokenListStub {
+    #[serde(rename = "AccessorID")]
+    pub accessor_id: String,
+    pub name: String,
+    #[serde(rename = "Type")]
+    pub token_type: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub policies: Vec<String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub roles: Vec<ACLTokenRoleLink>,
+    pub global: bool,
+    pub create_time: Option<DateTime<Utc>>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+// OneTimeToken can be exchanged once for the ACL token it was created from
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct OneTimeToken {
+    #[serde(rename = "OneTimeSecretID")]
+    pub one_time_secret_id: Secret<String>,
+    #[serde(rename = "AccessorID")]
+    pub accessor_id: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct OneTimeTokenUpsertResponse {
+    pub one_time_token: Option<OneTimeToken>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct OneTimeTokenExchangeRequest {
+    #[serde(rename = "OneTimeSecretID")]
+    pub one_time_secret_id: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct OneTimeTokenExchangeResponse {
+    pub token: Option<ACLToken>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct BootstrapRequest {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub bootstrap_secret: String,
+}
+
+// ACLPolicy grants capabilities through its HCL (or JSON) `rules`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLPolicy {
+    pub name: String,
+    pub description: String,
+    pub rules: String,
+    #[serde(rename = "JobACL", skip_serializing_if = "Option::is_none")]
+    pub job_acl: Option<JobACL>,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+impl ACLPolicy {
+    // Parse the rules of the policy into their structured form
+    pub fn parse_rules(&self) -> crate::error::Result<PolicyRules> {
+        PolicyRules::parse(&self.rules)
+    }
+}
+
+// JobACL associates a policy with a job, group or task workload identity
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct JobACL {
+    pub namespace: String,
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    pub group: String,
+    pub task: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLPolicyListStub {
+    pub name: String,
+    pub description: String,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLRolePolicyLink {
+    pub name: String,
+}
+
+// ACLRole groups policies so tokens can be granted them by role
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLRole {
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub policies: Vec<ACLRolePolicyLink>,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ACLRoleListStub {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub policies: Vec<ACLRolePolicyLink>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+//
+// Structured view of policy rules. Labeled blocks such as
+// `namespace "default" { ... }` are keyed by their label.
+//
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyRules {
+    #[serde(rename = "namespace")]
+    pub namespaces: HashMap<String, NamespacePolicyRule>,
+    #[serde(rename = "host_volume")]
+    pub host_volumes: HashMap<String, HostVolumePolicyRule>,
+    pub agent: Option<PolicyRule>,
+    pub node: Option<PolicyRule>,
+    pub operator: Option<PolicyRule>,
+    pub quota: Option<PolicyRule>,
+    pub plugin: Option<PolicyRule>,
+}
+
+impl PolicyRules {
+    // Rules may be written in either HCL or JSON
+    pub fn parse(rules: &str) -> crate::error::Result<PolicyRules> {
+        if rules.trim_start().starts_with('{') {
+            Ok(serde_json::from_str(rules)?)
+        } else {
+            Ok(hcl::from_str(rules)?)
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyRule {
+    pub policy: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamespacePolicyRule {
+    pub policy: Option<String>,
+    pub capabilities: Vec<String>,
+    pub variables: Option<VariablesPolicyRule>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VariablesPolicyRule {
+    #[serde(rename = "path")]
+    pub paths: HashMap<String, PathPolicyRule>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathPolicyRule {
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostVolumePolicyRule {
+    pub policy: Option<String>,
+    pub capabilities: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_acl_token() {
+        let js = r#"
+        {
+            "AccessorID": "b780e702-98ce-521f-2e5f-c6b87de05b24",
+            "SecretID": "3f4a0fcd-7c42-773c-25db-2d31ba0c05fe",
+            "Name": "Bootstrap Token",
+            "Type": "management",
+            "Policies": null,
+            "Roles": null,
+            "Global": true,
+            "CreateTime": "2017-08-23T22:47:14.695408057Z",
+            "ExpirationTime": null,
+            "CreateIndex": 7,
+            "ModifyIndex": 7
+        }
+        "#;
+
+        let token: ACLToken = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(token.token_type, ACL_TOKEN_TYPE_MANAGEMENT);
+        assert_eq!(
+            token.secret_id.as_str(),
+            "3f4a0fcd-7c42-773c-25db-2d31ba0c05fe"
+        );
+
+        let debug = format!("{:?}", token);
+        assert!(!debug.contains("3f4a0fcd"));
+        assert!(debug.contains("b780e702-98ce-521f-2e5f-c6b87de05b24"));
+        let debug = format!("{:#?}", token);
+        assert!(!debug.contains("3f4a0fcd"));
+    }
+
+    #[test]
+    fn parse_hcl_policy_rules() {
+        let policy = ACLPolicy {
+            name: "team-web".to_string(),
+            rules: r#"
+                namespace "default" {
+                    policy       = "read"
+                    capabilities = ["submit-job", "dispatch-job"]
+
+                    variables {
+                        path "web/*" {
+                            capabilities = ["read", "list"]
+                        }
+                    }
+                }
+
+                namespace "web" {
+                    policy = "write"
+                }
+
+                host_volume "prod-*" {
+                    policy = "read"
+                }
+
+                agent {
+                    policy = "read"
+                }
+
+                node {
+                    policy = "read"
+                }
+            "#
+            .to_string(),
+            ..Default::default()
+        };
+
+        let rules = policy.parse_rules().expect("parse failed");
+        assert_eq!(rules.namespaces.len(), 2);
+        let default = &rules.namespaces["default"];
+        assert_eq!(default.policy.as_deref(), Some("read"));
+        assert_eq!(default.capabilities, vec!["submit-job", "dispatch-job"]);
+        let variables = default.variables.as_ref().expect("variables");
+        assert_eq!(variables.paths["web/*"].capabilities, vec!["read", "list"]);
+        assert_eq!(rules.host_volumes["prod-*"].policy.as_deref(), Some("read"));
+        assert_eq!(rules.agent.and_then(|r| r.policy), Some("read".to_string()));
+        assert!(rules.operator.is_none());
+    }
+
+    #[test]
+    fn parse_json_policy_rules() {
+        let rules = PolicyRules::parse(r#"{"namespace": {"default": {"policy": "write"}}}"#)
+            .expect("parse failed");
+        assert_eq!(rules.namespaces["default"].policy.as_deref(), Some("write"));
+    }
+
+    #[test]
+    fn parse_invalid_policy_rules() {
+        assert!(PolicyRules::parse("namespace \"default\" {").is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
+
+use std::collections::HashMap;
+
+use super::serde_helpers::base64_bytes;
+
+pub const MEMBER_STATUS_ALIVE: &str = "alive";
+pub const MEMBER_STATUS_LEAVING: &str = "leaving";
+pub const MEMBER_STATUS_LEFT: &str = "left";
+pub const MEMBER_STATUS_FAILED: &str = "failed";
+
+// AgentMember is a server in the gossip pool as reported by the serf library
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AgentMember {
+    pub name: String,
+    pub addr: String,
+    pub port: u16,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub tags: HashMap<String, String>,
+    pub status: String,
+    pub protocol_min: u8,
+    pub protocol_max: u8,
+    pub protocol_cur: u8,
+    pub delegate_min: u8,
+    pub delegate_max: u8,
+    pub delegate_cur: u8,
+}
+
+impl AgentMember {
+    pub fn is_alive(&self) -> bool {
+        self.status == MEMBER_STATUS_ALIVE
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.tags.get("region").map(String::as_str)
+    }
+
+    pub fn datacenter(&self) -> Option<&str> {
+        self.tags.get("dc").map(String::as_str)
+    }
+
+    //
+    // The RPC address of the server in the same "ip:port" form returned by
+    // `/v1/status/leader` and `/v1/status/peers`
+    //
+    pub fn rpc_addr(&self) -> Option<String> {
+        let port = self.tags.get("port")?;
+        if self.addr.contains(':') {
+            Some(format!("[{}]:{}", self.addr, port))
+        } else {
+            Some(format!("{}:{}", self.addr, port))
+        }
+    }
+
+    //
+    // Is this member the leader given the address from `/v1/status/leader`
+    //
+    pub fn is_leader(&self, leader: &str) -> bool {
+        !leader.is_empty() && self.rpc_addr().is_some_and(|addr| addr == leader)
+    }
+}
+
+// ServerMembers is the response of `/v1/agent/members`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServerMembers {
+    pub server_name: String,
+    pub server_region: String,
+    #[serde(rename = "ServerDC")]
+    pub server_dc: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub members: Vec<AgentMember>,
+}
+
+impl ServerMembers {
+    // The member acting as leader given the address from `/v1/status/leader`
+    pub fn leader(&self, leader: &str) -> Option<&AgentMember> {
+        self.members.iter().find(|m| m.is_leader(leader))
+    }
+}
+
+// AgentSelf is the configuration and runtime stats of the queried agent
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "lowercase")]
+pub struct AgentSelf {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub config: HashMap<String, serde_json::Value>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub member: AgentMember,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub stats: HashMap<String, HashMap<String, String>>,
+}
+
+impl AgentSelf {
+    pub fn is_server(&self) -> bool {
+        self.stats.contains_key("nomad")
+    }
+
+    pub fn is_client(&self) -> bool {
+        self.stats.contains_key("client")
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "lowercase")]
+pub struct AgentHealth {
+    pub ok: bool,
+    pub message: String,
+}
+
+// AgentHealthResponse has an entry for the client and/or server running in
+// the agent
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "lowercase")]
+pub struct AgentHealthResponse {
+    pub client: Option<AgentHealth>,
+    pub server: Option<AgentHealth>,
+}
+
+impl AgentHealthResponse {
+    pub fn is_healthy(&self) -> bool {
+        let checks = [&self.client, &self.server];
+        checks.iter().any(|h| h.is_some()) && checks.iter().all(|h| h.as_ref().is_none_or(|h| h.ok))
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JoinResponse {
+    pub num_joined: u32,
+    pub error: String,
+}
+
+// StreamFrame is the framing used by streaming endpoints such as the agent
+// monitor; heartbeat frames are empty
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct StreamFrame {
+    pub offset: i64,
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+    pub file: String,
+    pub file_event: String,
+}
+
+impl StreamFrame {
+    pub fn is_heartbeat(&self) -> bool {
+        self.data.is_empty() && self.file_event.is_empty()
+    }
+}
+
+// LogEntry is a single line from the agent monitor in either the text or
+// JSON log format
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub module: String,
+    pub message: String,
+    pub fields: HashMap<String, serde_json::Value>,
+    pub raw: String,
+}
+
+impl LogEntry {
+    //
+    // Parse a line in the JSON format (`{"@level": ...}`) or the text format
+    // (`2021-03-01T10:11:12.123Z [INFO]  agent: message`). Lines which match
+    // neither are kept as the message.
+    //
+    pub fn parse(line: &str) -> Self {
+        let mut entry = LogEntry {
+            raw: line.to_string(),
+            ..Default::default()
+        };
+
+        if line.starts_with('{') {
+            if let Ok(mut fields) = serde_json::from_str::<HashMap<String, serde_json::Value>>(line)
+            {
+                let mut take = |key: &str| match fields.remove(key) {
+                    Some(serde_json::Value::String(s)) => s,
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                entry.timestamp = take("@timestamp");
+                entry.level = take("@level").to_uppercase();
+                entry.module = take("@module");
+                entry.message = take("@message");
+                entry.fields = fields;
+                return entry;
+            }
+        }
+
+        let (open, close) = match (line.find(" ["), line.find("] ")) {
+            (Some(open), Some(close)) if open < close => (open, close),
+            _ => {
+                entry.message = line.to_string();
+                return entry;
+            }
+        };
+        entry.timestamp = line[..open].to_string();
+        entry.level = line[open + 2..close].to_string();
+        let rest = line[close + 1..].trim_start();
+        match rest.find(": ") {
+            Some(colon) if !rest[..colon].contains(' ') => {
+                entry.module = rest[..colon].to_string();
+                entry.message = rest[colon + 2..].to_string();
+            }
+            _ => entry.message = rest.to_string(),
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_members() {
+        let js = r#"
+        {
+            "ServerName": "bacon-mac",
+            "ServerRegion": "global",
+            "ServerDC": "dc1",
+            "Members": [
+                {
+                    "Name": "bacon-mac.global",
+                    "Addr": "127.0.0.1",
+                    "Port": 4648,
+                    "Tags": {
+                        "mvn": "1",
+                        "build": "0.5.5dev",
+                        "port": "4647",
+                        "bootstrap": "1",
+                        "role": "nomad",
+                        "region": "global",
+                        "dc": "dc1",
+                        "vsn": "1"
+                    },
+                    "Status": "alive",
+                    "ProtocolMin": 1,
+                    "ProtocolMax": 5,
+                    "ProtocolCur": 2,
+                    "DelegateMin": 2,
+                    "DelegateMax": 4,
+                    "DelegateCur": 2
+                }
+            ]
+        }
+        "#;
+        let members: ServerMembers = serde_json::from_str(js).expect("deserialize failed");
+        let member = &members.members[0];
+        assert!(member.is_alive());
+        assert_eq!(member.region(), Some("global"));
+        assert_eq!(member.rpc_addr().as_deref(), Some("127.0.0.1:4647"));
+        assert!(members.leader("127.0.0.1:4647").is_some());
+        assert!(members.leader("").is_none());
+    }
+
+    #[test]
+    fn health() {
+        let js = r#"{"client": {"ok": true, "message": "ok"}, "server": {"ok": false, "message": "no leader"}}"#;
+        let health: AgentHealthResponse = serde_json::from_str(js).expect("deserialize failed");
+        assert!(!health.is_healthy());
+
+        let js = r#"{"server": {"ok": true, "message": "ok"}}"#;
+        let health: AgentHealthResponse = serde_json::from_str(js).expect("deserialize failed");
+        assert!(health.is_healthy());
+        assert!(!AgentHealthResponse::default().is_healthy());
+    }
+
+    #[test]
+    fn parse_log_lines() {
+        let entry = LogEntry::parse(
+            "2021-03-01T10:11:12.123-0800 [INFO]  nomad: serf: EventMemberJoin: server.global 10.0.0.1",
+        );
+        assert_eq!(entry.level, "INFO");
+        assert_eq!(entry.module, "nomad");
+        assert_eq!(
+            entry.message,
+            "serf: EventMemberJoin: server.global 10.0.0.1"
+        );
+
+        let entry = LogEntry::parse(
+            r#"{"@level":"debug","@message":"request complete","@module":"http","@timestamp":"2021-03-01T10:11:12.123456-08:00","duration":"1ms"}"#,
+        );
+        assert_eq!(entry.level, "DEBUG");
+        assert_eq!(entry.module, "http");
+        assert_eq!(entry.message, "request complete");
+        assert_eq!(entry.fields["duration"], "1ms");
+
+        let entry = LogEntry::parse("==> Nomad agent started!");
+        assert_eq!(entry.message, "==> Nomad agent started!");
+        assert!(entry.level.is_empty());
+    }
+}
@@ -9,6 +9,13 @@ use super::jobs::Job;
 use super::resources::{NetworkResource, Resources};
 use super::tasks::TaskState;
 
+pub const ALLOC_CLIENT_STATUS_PENDING: &str = "pending";
+pub const ALLOC_CLIENT_STATUS_RUNNING: &str = "running";
+pub const ALLOC_CLIENT_STATUS_COMPLETE: &str = "complete";
+pub const ALLOC_CLIENT_STATUS_FAILED: &str = "failed";
+pub const ALLOC_CLIENT_STATUS_LOST: &str = "lost";
+pub const ALLOC_CLIENT_STATUS_UNKNOWN: &str = "unknown";
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct Allocation {
@@ -59,6 +66,46 @@ pub struct Allocation {
     pub modify_time: i64,
 }
 
+// AllocationListStub is used to return a subset of alloc information during
+// list operations
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AllocationListStub {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "EvalID")]
+    pub eval_id: String,
+    pub name: String,
+    pub namespace: String,
+    #[serde(rename = "NodeID")]
+    pub node_id: String,
+    pub node_name: String,
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    pub job_type: String,
+    pub job_version: u64,
+    pub task_group: String,
+    pub desired_status: String,
+    pub desired_description: String,
+    pub client_status: String,
+    pub client_description: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub task_states: HashMap<String, TaskState>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub deployment_status: Option<AllocDeploymentStatus>,
+    #[serde(rename = "FollowupEvalID")]
+    pub followup_eval_id: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub reschedule_tracker: Option<RescheduleTracker>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub preempted_allocations: Vec<String>,
+    pub preempted_by_allocation: String,
+    pub create_index: u64,
+    pub modify_index: u64,
+    pub create_time: i64,
+    pub modify_time: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct AllocDeploymentStatus {
@@ -69,7 +116,7 @@ pub struct AllocDeploymentStatus {
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "PascalCase")]
+#[serde(default, rename_all = "PascalCase")]
 pub struct AllocationMetric {
     pub nodes_evaluated: i64,
     pub nodes_filtered: i64,
@@ -86,12 +133,131 @@ pub struct AllocationMetric {
     pub dimension_exhausted: HashMap<String, i64>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub quota_exhausted: Vec<String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub score_meta_data: Vec<NodeScoreMeta>,
+    pub coalesced_failures: i64,
     // NOTE: Deprecated fields omitted
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl AllocationMetric {
+    //
+    // Describe why placement failed, one "* " prefixed line per reason in the
+    // same form as the nomad cli placement failure output
+    //
+    pub fn explain(&self) -> String {
+        let mut lines = Vec::new();
+
+        if self.nodes_evaluated == 0 {
+            lines.push("* No nodes were eligible for evaluation".to_string());
+        }
+        for (dc, available) in sorted(&self.nodes_available) {
+            if *available == 0 {
+                lines.push(format!("* No nodes are available in datacenter \"{}\"", dc));
+            }
+        }
+
+        for (class, num) in sorted(&self.class_filtered) {
+            lines.push(format!(
+                "* Class \"{}\": {} nodes excluded by filter",
+                class, num
+            ));
+        }
+        for (constraint, num) in sorted(&self.constraint_filtered) {
+            lines.push(format!(
+                "* Constraint \"{}\": {} nodes excluded by filter",
+                constraint, num
+            ));
+        }
+
+        if self.nodes_exhausted > 0 {
+            lines.push(format!(
+                "* Resources exhausted on {} nodes",
+                self.nodes_exhausted
+            ));
+        }
+        for (class, num) in sorted(&self.class_exhausted) {
+            lines.push(format!("* Class \"{}\" exhausted on {} nodes", class, num));
+        }
+        for (dimension, num) in sorted(&self.dimension_exhausted) {
+            lines.push(format!(
+                "* Dimension \"{}\" exhausted on {} nodes",
+                dimension, num
+            ));
+        }
+
+        for dimension in self.quota_exhausted.iter() {
+            lines.push(format!("* Quota limit hit \"{}\"", dimension));
+        }
+
+        lines.join("\n")
+    }
+
+    //
+    // Tabulate the scores of the top ranked nodes, one row per node with a
+    // column per scoring dimension followed by the final normalized score
+    //
+    pub fn explain_scores(&self) -> String {
+        if self.score_meta_data.is_empty() {
+            return String::new();
+        }
+
+        let mut dimensions: Vec<&str> = self
+            .score_meta_data
+            .iter()
+            .flat_map(|meta| meta.scores.keys().map(String::as_str))
+            .collect();
+        dimensions.sort_unstable();
+        dimensions.dedup();
+
+        let mut rows = Vec::with_capacity(self.score_meta_data.len() + 1);
+        let mut header = vec!["Node".to_string()];
+        header.extend(dimensions.iter().map(|d| d.to_string()));
+        header.push("final score".to_string());
+        rows.push(header);
+
+        for meta in self.score_meta_data.iter() {
+            let mut row = vec![meta.node_id.clone()];
+            for dimension in dimensions.iter() {
+                row.push(match meta.scores.get(*dimension) {
+                    Some(score) => format!("{:.3}", score),
+                    None => "0".to_string(),
+                });
+            }
+            row.push(format!("{:.3}", meta.norm_score));
+            rows.push(row);
+        }
+
+        let mut widths = vec![0; rows[0].len()];
+        for row in rows.iter() {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.len());
+            }
+        }
+
+        rows.iter()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| format!("{:width$}", cell, width = widths[i]))
+                    .collect();
+                cells.join("  ").trim_end().to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn sorted(map: &HashMap<String, i64>) -> Vec<(&String, &i64)> {
+    let mut entries: Vec<(&String, &i64)> = map.iter().collect();
+    entries.sort();
+    entries
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NodeScoreMeta {
+    #[serde(rename = "NodeID")]
     pub node_id: String,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub scores: HashMap<String, f64>,
@@ -174,3 +340,88 @@ pub struct DesiredTransition {
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub reschedule: Option<bool>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explain_allocation_metric() {
+        let js = r#"
+        {
+            "NodesEvaluated": 8,
+            "NodesFiltered": 3,
+            "NodesAvailable": {
+                "dc1": 8,
+                "dc2": 0
+            },
+            "ClassFiltered": null,
+            "ConstraintFiltered": {
+                "${attr.kernel.name} = linux": 3
+            },
+            "NodesExhausted": 5,
+            "ClassExhausted": {
+                "large": 5
+            },
+            "DimensionExhausted": {
+                "memory": 5
+            },
+            "QuotaExhausted": null,
+            "ScoreMetaData": null,
+            "AllocationTime": 44236,
+            "CoalescedFailures": 2
+        }
+        "#;
+
+        let metric: AllocationMetric = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(metric.coalesced_failures, 2);
+        assert_eq!(
+            metric.explain(),
+            [
+                "* No nodes are available in datacenter \"dc2\"",
+                "* Constraint \"${attr.kernel.name} = linux\": 3 nodes excluded by filter",
+                "* Resources exhausted on 5 nodes",
+                "* Class \"large\" exhausted on 5 nodes",
+                "* Dimension \"memory\" exhausted on 5 nodes",
+            ]
+            .join("\n")
+        );
+        assert_eq!(metric.explain_scores(), "");
+    }
+
+    #[test]
+    fn explain_allocation_metric_scores() {
+        let js = r#"
+        {
+            "NodesEvaluated": 2,
+            "ScoreMetaData": [
+                {
+                    "NodeID": "47a4cc33-4bdc-a5f2-cdce-2a4017a58a72",
+                    "Scores": {
+                        "binpack": 0.3466,
+                        "job-anti-affinity": 0
+                    },
+                    "NormScore": 0.1733
+                },
+                {
+                    "NodeID": "9d0cbf44-5ef6-1a43-5cd1-8e5d4d3a0a5c",
+                    "Scores": {
+                        "binpack": 0.25
+                    },
+                    "NormScore": 0.25
+                }
+            ]
+        }
+        "#;
+
+        let metric: AllocationMetric = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(metric.explain(), "");
+        let table = metric.explain_scores();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Node "));
+        assert!(lines[0].ends_with("binpack  job-anti-affinity  final score"));
+        assert!(lines[1].starts_with("47a4cc33-4bdc-a5f2-cdce-2a4017a58a72  0.347"));
+        assert!(lines[2].ends_with("0.250"));
+    }
+}
@@ -0,0 +1,462 @@
+use std::time::Duration;
+
+use super::constraint::Constraint;
+use super::drivers::DriverConfig;
+use super::jobs::{Job, JobType, ParameterizedJobConfig, PeriodicConfig, UpdateStrategy};
+use super::resources::{NetworkResource, Resources};
+use super::services::Service;
+use super::tasks::{
+    Affinity, EphemeralDisk, LogConfig, MigrateStrategy, ReschedulePolicy, RestartPolicy, Spread,
+    Task, TaskArtifact, TaskGroup, TaskLifecycle, Template, VolumeMount, VolumeRequest,
+};
+use crate::error::Result;
+
+//
+// JobBuilder assembles a job from Rust code, for example
+//
+//     Job::service("web")
+//         .datacenters(["dc1"])
+//         .group(
+//             TaskGroup::builder("web")
+//                 .count(2)
+//                 .network(NetworkResource::bridge().with_mapped_port("http", 8080))
+//                 .service(Service::new("web").with_port("http"))
+//                 .task(Task::builder("server", "docker").config("image", "web:1.0")),
+//         )
+//         .build()?
+//
+// The job name defaults to the ID and group counts default to 1. `build`
+// checks the job with `Job::validate`.
+//
+#[derive(Debug, Clone)]
+pub struct JobBuilder {
+    job: Job,
+    groups: Vec<TaskGroupBuilder>,
+}
+
+impl JobBuilder {
+    pub fn new(id: &str, job_type: JobType) -> Self {
+        JobBuilder {
+            job: Job {
+                id: Some(id.to_string()),
+                job_type: Some(job_type),
+                ..Default::default()
+            },
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.job.name = Some(name.to_string());
+        self
+    }
+
+    pub fn region(mut self, region: &str) -> Self {
+        self.job.region = Some(region.to_string());
+        self
+    }
+
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.job.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn priority(mut self, priority: i64) -> Self {
+        self.job.priority = Some(priority);
+        self
+    }
+
+    pub fn datacenters<I, S>(mut self, datacenters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.job.datacenters = datacenters.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn all_at_once(mut self) -> Self {
+        self.job.all_at_once = true;
+        self
+    }
+
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.job.constraints.push(constraint);
+        self
+    }
+
+    pub fn affinity(mut self, affinity: Affinity) -> Self {
+        self.job.affinities.push(affinity);
+        self
+    }
+
+    pub fn spread(mut self, spread: Spread) -> Self {
+        self.job.spreads.push(spread);
+        self
+    }
+
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.job.meta.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn update(mut self, update: UpdateStrategy) -> Self {
+        self.job.update = Some(update);
+        self
+    }
+
+    pub fn reschedule(mut self, reschedule: ReschedulePolicy) -> Self {
+        self.job.reschedule = Some(reschedule);
+        self
+    }
+
+    pub fn migrate(mut self, migrate: MigrateStrategy) -> Self {
+        self.job.migrate = Some(migrate);
+        self
+    }
+
+    // Launch the job on a cron schedule, skipping a run while one is active
+    pub fn periodic(mut self, cron: &str) -> Self {
+        self.job.periodic = Some(PeriodicConfig {
+            enabled: Some(true),
+            spec: Some(cron.to_string()),
+            spec_type: Some("cron".to_string()),
+            prohibit_overlap: Some(true),
+            ..Default::default()
+        });
+        self
+    }
+
+    pub fn parameterized(mut self, parameterized: ParameterizedJobConfig) -> Self {
+        self.job.parameterized_job = Some(parameterized);
+        self
+    }
+
+    pub fn group(mut self, group: TaskGroupBuilder) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    pub fn build(self) -> Result<Job> {
+        let mut job = self.job;
+        if job.name.is_none() {
+            job.name = job.id.clone();
+        }
+        job.task_groups = self.groups.into_iter().map(|g| g.finish()).collect();
+        job.validate()?;
+        Ok(job)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskGroupBuilder {
+    group: TaskGroup,
+    tasks: Vec<TaskBuilder>,
+}
+
+impl TaskGroupBuilder {
+    pub fn new(name: &str) -> Self {
+        TaskGroupBuilder {
+            group: TaskGroup {
+                name: name.to_string(),
+                count: Some(1),
+                ..Default::default()
+            },
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn count(mut self, count: i64) -> Self {
+        self.group.count = Some(count);
+        self
+    }
+
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.group.constraints.push(constraint);
+        self
+    }
+
+    pub fn affinity(mut self, affinity: Affinity) -> Self {
+        self.group.affinities.push(affinity);
+        self
+    }
+
+    pub fn spread(mut self, spread: Spread) -> Self {
+        self.group.spreads.push(spread);
+        self
+    }
+
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.group.meta.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn network(mut self, network: NetworkResource) -> Self {
+        self.group.networks.push(network);
+        self
+    }
+
+    pub fn service(mut self, service: Service) -> Self {
+        self.group.services.push(service);
+        self
+    }
+
+    // Declare a volume for tasks to mount with `TaskBuilder::volume_mount`
+    pub fn volume(mut self, name: &str, mut volume: VolumeRequest) -> Self {
+        volume.name = name.to_string();
+        self.group.volumes.insert(name.to_string(), volume);
+        self
+    }
+
+    pub fn restart(mut self, restart: RestartPolicy) -> Self {
+        self.group.restart_policy = Some(restart);
+        self
+    }
+
+    pub fn reschedule(mut self, reschedule: ReschedulePolicy) -> Self {
+        self.group.reschedule_policy = Some(reschedule);
+        self
+    }
+
+    pub fn ephemeral_disk(mut self, size_mb: i64) -> Self {
+        self.group.ephemeral_disk = Some(EphemeralDisk {
+            size_mb: Some(size_mb),
+            ..Default::default()
+        });
+        self
+    }
+
+    pub fn update(mut self, update: UpdateStrategy) -> Self {
+        self.group.update = Some(update);
+        self
+    }
+
+    pub fn migrate(mut self, migrate: MigrateStrategy) -> Self {
+        self.group.migrate = Some(migrate);
+        self
+    }
+
+    pub fn shutdown_delay(mut self, delay: Duration) -> Self {
+        self.group.shutdown_delay = Some(delay);
+        self
+    }
+
+    pub fn task(mut self, task: TaskBuilder) -> Self {
+        self.tasks.push(task);
+        self
+    }
+
+    pub fn build(self) -> Result<TaskGroup> {
+        let group = self.finish();
+        group.validate()?;
+        Ok(group)
+    }
+
+    fn finish(self) -> TaskGroup {
+        let mut group = self.group;
+        group.tasks = self.tasks.into_iter().map(|t| t.task).collect();
+        group
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskBuilder {
+    task: Task,
+}
+
+impl TaskBuilder {
+    pub fn new(name: &str, driver: &str) -> Self {
+        TaskBuilder {
+            task: Task {
+                name: name.to_string(),
+                driver: Some(driver.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn user(mut self, user: &str) -> Self {
+        self.task.user = Some(user.to_string());
+        self
+    }
+
+    // Replace the driver and its options with a typed config
+    pub fn driver_config(mut self, config: impl Into<DriverConfig>) -> Self {
+        self.task.set_driver_config(config.into());
+        self
+    }
+
+    // Set a driver option, for example `.config("image", "redis:7")`
+    pub fn config(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.task.config.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.task.env.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.task.meta.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.task.constraints.push(constraint);
+        self
+    }
+
+    pub fn affinity(mut self, affinity: Affinity) -> Self {
+        self.task.affinities.push(affinity);
+        self
+    }
+
+    pub fn service(mut self, service: Service) -> Self {
+        self.task.services.push(service);
+        self
+    }
+
+    pub fn resources(mut self, resources: Resources) -> Self {
+        self.task.resources = Some(resources);
+        self
+    }
+
+    pub fn template(mut self, template: Template) -> Self {
+        self.task.templates.push(template);
+        self
+    }
+
+    pub fn artifact(mut self, artifact: TaskArtifact) -> Self {
+        self.task.artifacts.push(artifact);
+        self
+    }
+
+    // Mount the group volume `volume` at `destination` inside the task
+    pub fn volume_mount(mut self, volume: &str, destination: &str) -> Self {
+        self.task.volume_mounts.push(VolumeMount {
+            volume: Some(volume.to_string()),
+            destination: Some(destination.to_string()),
+            ..Default::default()
+        });
+        self
+    }
+
+    // Run the task at a lifecycle hook such as "prestart" rather than as a
+    // main task, a sidecar keeps running alongside the main tasks
+    pub fn lifecycle(mut self, hook: &str, sidecar: bool) -> Self {
+        self.task.lifecycle = Some(TaskLifecycle {
+            hook: Some(hook.to_string()),
+            sidecar,
+        });
+        self
+    }
+
+    // Stop the other tasks in the group when this one exits
+    pub fn leader(mut self) -> Self {
+        self.task.leader = true;
+        self
+    }
+
+    pub fn restart(mut self, restart: RestartPolicy) -> Self {
+        self.task.restart_policy = Some(restart);
+        self
+    }
+
+    pub fn log_config(mut self, log_config: LogConfig) -> Self {
+        self.task.log_config = Some(log_config);
+        self
+    }
+
+    pub fn kill_timeout(mut self, timeout: Duration) -> Self {
+        self.task.kill_timeout = Some(timeout);
+        self
+    }
+
+    pub fn kill_signal(mut self, signal: &str) -> Self {
+        self.task.kill_signal = Some(signal.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<Task> {
+        self.task.validate()?;
+        Ok(self.task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::services::ServiceCheck;
+    use serde_json::json;
+
+    #[test]
+    fn build_job() {
+        let job =
+            Job::service("web")
+                .datacenters(["dc1", "dc2"])
+                .constraint(Constraint::new("${attr.kernel.name}", "=", "linux"))
+                .group(
+                    TaskGroup::builder("web")
+                        .count(2)
+                        .network(NetworkResource::bridge().with_mapped_port("http", 8080))
+                        .service(Service::new("web").with_port("http").with_check(
+                            ServiceCheck::http(
+                                "/health",
+                                Duration::from_secs(10),
+                                Duration::from_secs(2),
+                            ),
+                        ))
+                        .volume("data", VolumeRequest::host("web-data"))
+                        .task(
+                            Task::builder("server", "docker")
+                                .config("image", "web:1.0")
+                                .config("ports", json!(["http"]))
+                                .env("PORT", "8080")
+                                .resources(Resources::new(200, 128))
+                                .volume_mount("data", "/data")
+                                .template(Template::inline("{{ key \"web\" }}", "local/web.conf")),
+                        ),
+                )
+                .build()
+                .expect("build");
+
+        assert_eq!(job.name.as_deref(), Some("web"));
+        let js = serde_json::to_value(&job).expect("serialize");
+        assert_eq!(js["Type"], "service");
+        assert_eq!(js["Datacenters"], json!(["dc1", "dc2"]));
+        assert_eq!(js["Constraints"][0]["LTarget"], "${attr.kernel.name}");
+        let group = &js["TaskGroups"][0];
+        assert_eq!(group["Count"], 2);
+        assert_eq!(group["Networks"][0]["Mode"], "bridge");
+        assert_eq!(group["Networks"][0]["DynamicPorts"][0]["To"], 8080);
+        assert_eq!(group["Services"][0]["PortLabel"], "http");
+        assert_eq!(group["Volumes"]["data"]["Name"], "data");
+        let task = &group["Tasks"][0];
+        assert_eq!(task["Driver"], "docker");
+        assert_eq!(task["Config"]["ports"], json!(["http"]));
+        assert_eq!(task["Env"]["PORT"], "8080");
+        assert_eq!(task["Resources"]["MemoryMB"], 128);
+        assert_eq!(task["Templates"][0]["DestPath"], "local/web.conf");
+    }
+
+    #[test]
+    fn build_reports_every_problem() {
+        let result = Job::batch("report")
+            .group(
+                TaskGroup::builder("run")
+                    .task(Task::builder("report", ""))
+                    .task(Task::builder("report", "exec")),
+            )
+            .group(TaskGroup::builder("run"))
+            .build();
+        let error = result.expect_err("invalid job");
+        assert_eq!(
+            error.to_string(),
+            "invalid job: TaskGroups[0].Tasks[0].Driver: missing task driver; \
+             TaskGroups[0].Tasks[1].Name: duplicate task \"report\"; \
+             TaskGroups[1].Name: duplicate group \"run\"; \
+             TaskGroups[1].Tasks: missing tasks"
+        );
+    }
+}
@@ -0,0 +1,584 @@
+use std::time::Duration;
+
+use super::jobs::{
+    Job, JobType, UpdateStrategy, DEFAULT_NAMESPACE, DISPATCH_PAYLOAD_OPTIONAL, GLOBAL_REGION,
+};
+use super::resources::NetworkResource;
+use super::scaling::SCALING_POLICY_TYPE_HORIZONTAL;
+use super::services::Service;
+use super::tasks::{
+    Affinity, EphemeralDisk, LogConfig, MigrateStrategy, ReschedulePolicy, RestartPolicy, Spread,
+    Task, TaskArtifact, TaskGroup, Template,
+};
+
+const DEFAULT_PRIORITY: i64 = 50;
+const DEFAULT_WEIGHT: i8 = 50;
+const DEFAULT_HOST_NETWORK: &str = "default";
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+
+impl Job {
+    //
+    // Fill unset fields with the defaults the server applies when a job is
+    // registered, mirroring `Job.Canonicalize` in Nomad's api package. A
+    // canonicalized spec can be compared with a job read back from the
+    // server without every default showing up as a difference. Fields that
+    // are already set, including those inherited by groups and tasks, are
+    // kept.
+    //
+    pub fn canonicalize(&mut self) {
+        fill(&mut self.name, &self.id.clone());
+        fill_with(&mut self.region, GLOBAL_REGION.to_string());
+        fill_with(&mut self.namespace, DEFAULT_NAMESPACE.to_string());
+        fill_with(&mut self.job_type, JobType::Service);
+        fill_with(&mut self.priority, DEFAULT_PRIORITY);
+
+        if let Some(periodic) = &mut self.periodic {
+            fill_with(&mut periodic.enabled, true);
+            fill_with(&mut periodic.spec_type, "cron".to_string());
+            fill_with(&mut periodic.prohibit_overlap, false);
+            fill_with(&mut periodic.time_zone, "UTC".to_string());
+        }
+        if let Some(parameterized) = &mut self.parameterized_job {
+            fill_with(
+                &mut parameterized.payload,
+                DISPATCH_PAYLOAD_OPTIONAL.to_string(),
+            );
+        }
+        match &mut self.update {
+            Some(update) => fill_update(update, &default_update()),
+            None if self.job_type == Some(JobType::Service) => self.update = Some(default_update()),
+            None => {}
+        }
+        affinities(&mut self.affinities);
+        spreads(&mut self.spreads);
+
+        let mut groups = std::mem::take(&mut self.task_groups);
+        for group in &mut groups {
+            self.canonicalize_group(group);
+        }
+        self.task_groups = groups;
+    }
+
+    fn canonicalize_group(&self, group: &mut TaskGroup) {
+        let job_type = self.job_type.clone().unwrap_or_default();
+        let min = group.scaling.as_ref().and_then(|scaling| scaling.min);
+        fill_with(&mut group.count, min.unwrap_or(1));
+        if let Some(scaling) = &mut group.scaling {
+            fill_with(&mut scaling.enabled, true);
+            fill(&mut scaling.min, &group.count);
+            fill_with(
+                &mut scaling.policy_type,
+                SCALING_POLICY_TYPE_HORIZONTAL.to_string(),
+            );
+        }
+
+        let disk = group.ephemeral_disk.get_or_insert_with(Default::default);
+        fill_disk(disk, &default_ephemeral_disk());
+
+        // Groups inherit the job's update, reschedule and migrate settings.
+        // An update strategy is only given to a group when the job or the
+        // group sets one.
+        match (&mut group.update, &self.update) {
+            (Some(update), Some(job_update)) => fill_update(update, job_update),
+            (None, Some(job_update)) if !is_empty_update(job_update) => {
+                group.update = Some(job_update.clone())
+            }
+            _ => {}
+        }
+        if let Some(update) = &mut group.update {
+            fill_update(update, &default_update());
+        }
+
+        let policy = group.reschedule_policy.get_or_insert_with(Default::default);
+        if let Some(job_policy) = &self.reschedule {
+            fill_reschedule(policy, job_policy);
+        }
+        fill_reschedule(policy, &default_reschedule(&job_type));
+
+        group.migrate = match job_type {
+            JobType::Service => {
+                let mut migrate = group.migrate.take().unwrap_or_default();
+                if let Some(job_migrate) = &self.migrate {
+                    fill_migrate(&mut migrate, job_migrate);
+                }
+                fill_migrate(&mut migrate, &default_migrate());
+                Some(migrate)
+            }
+            _ => None,
+        };
+
+        let restart = group.restart_policy.get_or_insert_with(Default::default);
+        fill_restart(restart, &default_restart(&job_type));
+
+        affinities(&mut group.affinities);
+        spreads(&mut group.spreads);
+        networks(&mut group.networks);
+        services(&mut group.services);
+        for task in &mut group.tasks {
+            canonicalize_task(task, group.restart_policy.as_ref());
+        }
+    }
+}
+
+// Tasks inherit the restart policy of their group
+fn canonicalize_task(task: &mut Task, restart: Option<&RestartPolicy>) {
+    let resources = task.resources.get_or_insert_with(Default::default);
+    fill_with(&mut resources.cpu, 100);
+    fill_with(&mut resources.memory_mb, 300);
+    networks(&mut resources.networks);
+
+    fill_with(&mut task.kill_timeout, Duration::from_secs(5));
+
+    let defaults = LogConfig::default();
+    let log_config = task.log_config.get_or_insert_with(LogConfig::default);
+    fill(&mut log_config.max_files, &defaults.max_files);
+    fill(&mut log_config.max_file_size_mb, &defaults.max_file_size_mb);
+
+    if let Some(restart) = restart {
+        let task_restart = task.restart_policy.get_or_insert_with(Default::default);
+        fill_restart(task_restart, restart);
+    }
+    if task
+        .lifecycle
+        .as_ref()
+        .is_some_and(|l| l.hook.as_deref().unwrap_or_default().is_empty())
+    {
+        task.lifecycle = None;
+    }
+    if let Some(vault) = &mut task.vault {
+        fill_with(&mut vault.change_mode, "restart".to_string());
+    }
+
+    task.templates.iter_mut().for_each(template);
+    task.artifacts.iter_mut().for_each(artifact);
+    affinities(&mut task.affinities);
+    services(&mut task.services);
+}
+
+fn template(template: &mut Template) {
+    fill_with(&mut template.change_mode, "restart".to_string());
+    fill_with(&mut template.splay, Duration::from_secs(5));
+    fill_with(&mut template.perms, "0644".to_string());
+    fill_with(&mut template.left_delim, "{{".to_string());
+    fill_with(&mut template.right_delim, "}}".to_string());
+    if let Some(signal) = &mut template.change_signal {
+        *signal = signal.to_uppercase();
+    }
+}
+
+// Artifacts download into the task's local directory
+fn artifact(artifact: &mut TaskArtifact) {
+    fill_with(&mut artifact.getter_mode, "any".to_string());
+    if artifact.relative_dest.is_none() {
+        let dest = match artifact.getter_mode.as_deref() {
+            Some("file") => {
+                let source = artifact.getter_source.as_deref().unwrap_or_default();
+                let name = source.rsplit('/').next().unwrap_or_default();
+                format!("local/{}", name)
+            }
+            _ => "local/".to_string(),
+        };
+        artifact.relative_dest = Some(dest);
+    }
+}
+
+fn affinities(affinities: &mut [Affinity]) {
+    for affinity in affinities {
+        fill_with(&mut affinity.weight, DEFAULT_WEIGHT);
+    }
+}
+
+fn spreads(spreads: &mut [Spread]) {
+    for spread in spreads {
+        fill_with(&mut spread.weight, DEFAULT_WEIGHT);
+    }
+}
+
+// Ports are placed on the default host network unless one is given
+fn networks(networks: &mut [NetworkResource]) {
+    for network in networks {
+        let ports = network.reserved_ports.iter_mut();
+        for port in ports.chain(network.dynamic_ports.iter_mut()) {
+            fill_with(&mut port.host_network, DEFAULT_HOST_NETWORK.to_string());
+        }
+    }
+}
+
+fn services(services: &mut [Service]) {
+    for service in services {
+        fill_with(&mut service.address_mode, "auto".to_string());
+    }
+}
+
+fn default_update() -> UpdateStrategy {
+    UpdateStrategy {
+        stagger: Some(Duration::from_secs(30)),
+        max_parallel: Some(1),
+        health_check: Some("checks".to_string()),
+        min_healthy_time: Some(Duration::from_secs(10)),
+        healthy_deadline: Some(Duration::from_secs(5 * MINUTE)),
+        progress_deadline: Some(Duration::from_secs(10 * MINUTE)),
+        canary: Some(0),
+        auto_revert: Some(false),
+        auto_promote: Some(false),
+    }
+}
+
+fn default_reschedule(job_type: &JobType) -> ReschedulePolicy {
+    match job_type {
+        JobType::Batch => ReschedulePolicy {
+            attempts: Some(1),
+            interval: Some(Duration::from_secs(24 * HOUR)),
+            delay: Some(Duration::from_secs(5)),
+            delay_function: Some("constant".to_string()),
+            max_delay: Some(Duration::ZERO),
+            unlimited: Some(false),
+        },
+        // System jobs are not rescheduled, their allocations are replaced
+        // on the same node
+        JobType::System => ReschedulePolicy {
+            attempts: Some(0),
+            interval: Some(Duration::ZERO),
+            delay: Some(Duration::ZERO),
+            delay_function: Some(String::new()),
+            max_delay: Some(Duration::ZERO),
+            unlimited: Some(false),
+        },
+        _ => ReschedulePolicy {
+            attempts: Some(0),
+            interval: Some(Duration::ZERO),
+            delay: Some(Duration::from_secs(30)),
+            delay_function: Some("exponential".to_string()),
+            max_delay: Some(Duration::from_secs(HOUR)),
+            unlimited: Some(true),
+        },
+    }
+}
+
+fn is_empty_update(update: &UpdateStrategy) -> bool {
+    update.stagger.is_none()
+        && update.max_parallel.is_none()
+        && update.health_check.is_none()
+        && update.min_healthy_time.is_none()
+        && update.healthy_deadline.is_none()
+        && update.progress_deadline.is_none()
+        && update.canary.is_none()
+        && update.auto_revert.is_none()
+        && update.auto_promote.is_none()
+}
+
+fn default_migrate() -> MigrateStrategy {
+    MigrateStrategy {
+        max_parallel: Some(1),
+        health_check: Some("checks".to_string()),
+        min_healthy_time: Some(Duration::from_secs(10)),
+        healthy_deadline: Some(Duration::from_secs(5 * MINUTE)),
+    }
+}
+
+fn default_restart(job_type: &JobType) -> RestartPolicy {
+    match job_type {
+        JobType::Batch => RestartPolicy {
+            interval: Some(Duration::from_secs(24 * HOUR)),
+            attempts: Some(3),
+            delay: Some(Duration::from_secs(15)),
+            mode: Some("fail".to_string()),
+        },
+        _ => RestartPolicy {
+            interval: Some(Duration::from_secs(30 * MINUTE)),
+            attempts: Some(2),
+            delay: Some(Duration::from_secs(15)),
+            mode: Some("fail".to_string()),
+        },
+    }
+}
+
+fn default_ephemeral_disk() -> EphemeralDisk {
+    EphemeralDisk {
+        sticky: Some(false),
+        migrate: Some(false),
+        size_mb: Some(300),
+    }
+}
+
+//
+// Each fill_* function copies the fields set in `from` into those left unset
+// in `into`, which both applies defaults and merges inherited settings
+//
+
+fn fill_update(into: &mut UpdateStrategy, from: &UpdateStrategy) {
+    fill(&mut into.stagger, &from.stagger);
+    fill(&mut into.max_parallel, &from.max_parallel);
+    fill(&mut into.health_check, &from.health_check);
+    fill(&mut into.min_healthy_time, &from.min_healthy_time);
+    fill(&mut into.healthy_deadline, &from.healthy_deadline);
+    fill(&mut into.progress_deadline, &from.progress_deadline);
+    fill(&mut into.canary, &from.canary);
+    fill(&mut into.auto_revert, &from.auto_revert);
+    fill(&mut into.auto_promote, &from.auto_promote);
+}
+
+fn fill_reschedule(into: &mut ReschedulePolicy, from: &ReschedulePolicy) {
+    fill(&mut into.attempts, &from.attempts);
+    fill(&mut into.interval, &from.interval);
+    fill(&mut into.delay, &from.delay);
+    fill(&mut into.delay_function, &from.delay_function);
+    fill(&mut into.max_delay, &from.max_delay);
+    fill(&mut into.unlimited, &from.unlimited);
+}
+
+fn fill_migrate(into: &mut MigrateStrategy, from: &MigrateStrategy) {
+    fill(&mut into.max_parallel, &from.max_parallel);
+    fill(&mut into.health_check, &from.health_check);
+    fill(&mut into.min_healthy_time, &from.min_healthy_time);
+    fill(&mut into.healthy_deadline, &from.healthy_deadline);
+}
+
+fn fill_restart(into: &mut RestartPolicy, from: &RestartPolicy) {
+    fill(&mut into.interval, &from.interval);
+    fill(&mut into.attempts, &from.attempts);
+    fill(&mut into.delay, &from.delay);
+    fill(&mut into.mode, &from.mode);
+}
+
+fn fill_disk(into: &mut EphemeralDisk, from: &EphemeralDisk) {
+    fill(&mut into.sticky, &from.sticky);
+    fill(&mut into.migrate, &from.migrate);
+    fill(&mut into.size_mb, &from.size_mb);
+}
+
+fn fill<T: Clone>(value: &mut Option<T>, default: &Option<T>) {
+    if value.is_none() {
+        *value = default.clone();
+    }
+}
+
+fn fill_with<T>(value: &mut Option<T>, default: T) {
+    value.get_or_insert(default);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::scaling::ScalingPolicy;
+    use crate::model::tasks::Task;
+
+    #[test]
+    fn canonicalize_job() {
+        let mut job = Job::service("web")
+            .update(UpdateStrategy {
+                max_parallel: Some(2),
+                ..Default::default()
+            })
+            .group(
+                TaskGroup::builder("web")
+                    .network(NetworkResource::bridge().with_mapped_port("http", 8080))
+                    .restart(RestartPolicy {
+                        attempts: Some(5),
+                        ..Default::default()
+                    })
+                    .task(
+                        Task::builder("server", "docker")
+                            .template(Template::inline("{{ key \"web\" }}", "local/web.conf"))
+                            .restart(RestartPolicy {
+                                mode: Some("delay".to_string()),
+                                ..Default::default()
+                            }),
+                    ),
+            )
+            .build()
+            .expect("build");
+        job.task_groups.push(TaskGroup {
+            name: "workers".to_string(),
+            scaling: Some(ScalingPolicy {
+                min: Some(2),
+                max: Some(10),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        job.canonicalize();
+
+        assert_eq!(job.region.as_deref(), Some("global"));
+        assert_eq!(job.namespace.as_deref(), Some("default"));
+        assert_eq!(job.priority, Some(50));
+
+        let group = &job.task_groups[0];
+        let update = group.update.as_ref().expect("group update");
+        assert_eq!(update.max_parallel, Some(2));
+        assert_eq!(update.healthy_deadline, Some(Duration::from_secs(300)));
+        assert_eq!(group.ephemeral_disk.as_ref().unwrap().size_mb, Some(300));
+        assert_eq!(
+            group.networks[0].dynamic_ports[0].host_network.as_deref(),
+            Some("default")
+        );
+        assert_eq!(group.migrate.as_ref().unwrap().max_parallel, Some(1));
+        assert_eq!(
+            group
+                .reschedule_policy
+                .as_ref()
+                .unwrap()
+                .delay_function
+                .as_deref(),
+            Some("exponential")
+        );
+
+        let task = &group.tasks[0];
+        let restart = task.restart_policy.as_ref().expect("task restart");
+        assert_eq!(restart.attempts, Some(5));
+        assert_eq!(restart.mode.as_deref(), Some("delay"));
+        assert_eq!(restart.interval, Some(Duration::from_secs(1800)));
+        assert_eq!(task.resources.as_ref().unwrap().memory_mb, Some(300));
+        assert_eq!(task.log_config.as_ref().unwrap().max_files, Some(10));
+        assert_eq!(task.templates[0].perms.as_deref(), Some("0644"));
+
+        // Without a count a group starts at its scaling minimum
+        let workers = &job.task_groups[1];
+        assert_eq!(workers.count, Some(2));
+        let scaling = workers.scaling.as_ref().unwrap();
+        assert_eq!(scaling.enabled, Some(true));
+        assert_eq!(scaling.policy_type.as_deref(), Some("horizontal"));
+
+        let once = serde_json::to_value(&job).unwrap();
+        job.canonicalize();
+        assert_eq!(serde_json::to_value(&job).unwrap(), once);
+    }
+
+    fn value<T: serde::Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).expect("serialize")
+    }
+
+    #[test]
+    fn canonicalize_matches_server_job() {
+        // As returned by GET /v1/job/:job_id for the same spec
+        let server: Job = serde_json::from_value(serde_json::json!({
+            "ID": "web",
+            "Name": "web",
+            "Type": "service",
+            "Update": {
+                "Stagger": 30000000000u64,
+                "MaxParallel": 1,
+                "HealthCheck": "checks",
+                "MinHealthyTime": 10000000000u64,
+                "HealthyDeadline": 300000000000u64,
+                "ProgressDeadline": 600000000000u64,
+                "Canary": 0,
+                "AutoRevert": false,
+                "AutoPromote": false
+            },
+            "TaskGroups": [{
+                "Name": "web",
+                "RestartPolicy": {
+                    "Interval": 1800000000000u64,
+                    "Attempts": 2,
+                    "Delay": 15000000000u64,
+                    "Mode": "fail"
+                },
+                "ReschedulePolicy": {
+                    "Attempts": 0,
+                    "Interval": 0,
+                    "Delay": 30000000000u64,
+                    "DelayFunction": "exponential",
+                    "MaxDelay": 3600000000000u64,
+                    "Unlimited": true
+                },
+                "Tasks": [{
+                    "Name": "server",
+                    "Driver": "docker",
+                    "KillTimeout": 5000000000u64
+                }]
+            }]
+        }))
+        .expect("server job");
+
+        let mut job = Job::service("web")
+            .group(TaskGroup::builder("web").task(Task::builder("server", "docker")))
+            .build()
+            .expect("build");
+        job.canonicalize();
+
+        let (group, server_group) = (&job.task_groups[0], &server.task_groups[0]);
+        assert_eq!(value(&job.update), value(&server.update));
+        assert_eq!(
+            value(&group.restart_policy),
+            value(&server_group.restart_policy)
+        );
+        assert_eq!(
+            value(&group.reschedule_policy),
+            value(&server_group.reschedule_policy)
+        );
+        assert_eq!(
+            group.tasks[0].kill_timeout,
+            server_group.tasks[0].kill_timeout
+        );
+
+        // A system job without an update strategy, whose allocations are
+        // never rescheduled
+        let server: Job = serde_json::from_value(serde_json::json!({
+            "ID": "agent",
+            "Name": "agent",
+            "Type": "system",
+            "TaskGroups": [{
+                "Name": "agent",
+                "Update": null,
+                "RestartPolicy": {
+                    "Interval": 1800000000000u64,
+                    "Attempts": 2,
+                    "Delay": 15000000000u64,
+                    "Mode": "fail"
+                },
+                "ReschedulePolicy": {
+                    "Attempts": 0,
+                    "Interval": 0,
+                    "Delay": 0,
+                    "DelayFunction": "",
+                    "MaxDelay": 0,
+                    "Unlimited": false
+                },
+                "Migrate": null,
+                "Tasks": [{
+                    "Name": "agent",
+                    "Driver": "exec",
+                    "KillTimeout": 5000000000u64
+                }]
+            }]
+        }))
+        .expect("server system job");
+
+        let mut job = Job::system("agent")
+            .group(TaskGroup::builder("agent").task(Task::builder("agent", "exec")))
+            .build()
+            .expect("build");
+        job.canonicalize();
+
+        let (group, server_group) = (&job.task_groups[0], &server.task_groups[0]);
+        assert_eq!(value(&group.update), value(&server_group.update));
+        assert_eq!(
+            value(&group.restart_policy),
+            value(&server_group.restart_policy)
+        );
+        assert_eq!(
+            value(&group.reschedule_policy),
+            value(&server_group.reschedule_policy)
+        );
+        assert_eq!(value(&group.migrate), value(&server_group.migrate));
+    }
+
+    #[test]
+    fn canonicalize_batch_job() {
+        let mut job = Job::batch("report")
+            .group(TaskGroup::builder("report").task(Task::builder("report", "exec")))
+            .build()
+            .expect("build");
+        job.canonicalize();
+
+        assert!(job.update.is_none());
+        let group = &job.task_groups[0];
+        assert!(group.update.is_none());
+        assert!(group.migrate.is_none());
+        assert_eq!(group.reschedule_policy.as_ref().unwrap().attempts, Some(1));
+        assert_eq!(group.restart_policy.as_ref().unwrap().attempts, Some(3));
+    }
+}
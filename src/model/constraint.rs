@@ -21,3 +21,14 @@ pub struct Constraint {
     pub r_target: Option<String>,
     pub operand: Option<String>, // FIXME: Use Operand enum
 }
+
+impl Constraint {
+    // A constraint such as `Constraint::new("${attr.kernel.name}", "=", "linux")`
+    pub fn new(attribute: &str, operand: &str, value: &str) -> Self {
+        Constraint {
+            l_target: Some(attribute.to_string()),
+            r_target: Some(value.to_string()),
+            operand: Some(operand.to_string()),
+        }
+    }
+}
@@ -1,6 +1,13 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::rust::default_on_null;
 
+use std::collections::HashMap;
+
+use super::allocations::{Allocation, AllocationListStub};
+use super::nodes::{CSIInfo, CSITopology};
+use super::secret::Secret;
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct CSIMountOptions {
@@ -13,3 +20,262 @@ pub struct CSIMountOptions {
     )]
     pub extra_keys_hcl: Vec<String>,
 }
+
+pub const CSI_VOLUME_ACCESS_MODE_SINGLE_NODE_READER: &str = "single-node-reader-only";
+pub const CSI_VOLUME_ACCESS_MODE_SINGLE_NODE_WRITER: &str = "single-node-writer";
+pub const CSI_VOLUME_ACCESS_MODE_MULTI_NODE_READER: &str = "multi-node-reader-only";
+pub const CSI_VOLUME_ACCESS_MODE_MULTI_NODE_SINGLE_WRITER: &str = "multi-node-single-writer";
+pub const CSI_VOLUME_ACCESS_MODE_MULTI_NODE_MULTI_WRITER: &str = "multi-node-multi-writer";
+
+pub const CSI_VOLUME_ATTACHMENT_MODE_FILESYSTEM: &str = "file-system";
+pub const CSI_VOLUME_ATTACHMENT_MODE_BLOCK_DEVICE: &str = "block-device";
+
+// CSISecrets are passed to the plugin and never returned by the API in full
+pub type CSISecrets = HashMap<String, Secret<String>>;
+
+// CSIVolumeCapability is an access and attachment mode a volume must support
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSIVolumeCapability {
+    pub access_mode: String,
+    pub attachment_mode: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSITopologyRequest {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub required: Vec<CSITopology>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub preferred: Vec<CSITopology>,
+}
+
+// CSIVolume is a volume provided by a CSI plugin, either registered from an
+// existing external volume or created through the plugin
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSIVolume {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "ExternalID")]
+    pub external_id: String,
+    pub namespace: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub topologies: Vec<CSITopology>,
+    pub access_mode: String,
+    pub attachment_mode: String,
+    pub mount_options: Option<CSIMountOptions>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub secrets: CSISecrets,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub parameters: HashMap<String, String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub context: HashMap<String, String>,
+    pub capacity: i64,
+    pub requested_capacity_min: i64,
+    pub requested_capacity_max: i64,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub requested_capabilities: Vec<CSIVolumeCapability>,
+    #[serde(rename = "CloneID")]
+    pub clone_id: String,
+    #[serde(rename = "SnapshotID")]
+    pub snapshot_id: String,
+    pub requested_topologies: Option<CSITopologyRequest>,
+
+    // Server managed fields
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub read_allocs: HashMap<String, Option<Allocation>>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub write_allocs: HashMap<String, Option<Allocation>>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub allocations: Vec<AllocationListStub>,
+    pub schedulable: bool,
+    #[serde(rename = "PluginID")]
+    pub plugin_id: String,
+    pub provider: String,
+    pub provider_version: String,
+    pub controller_required: bool,
+    pub controllers_healthy: i64,
+    pub controllers_expected: i64,
+    pub nodes_healthy: i64,
+    pub nodes_expected: i64,
+    pub resource_exhausted: Option<DateTime<Utc>>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+impl CSIVolume {
+    // The number of allocations holding a read and a write claim
+    pub fn claims(&self) -> (usize, usize) {
+        (self.read_allocs.len(), self.write_allocs.len())
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSIVolumeListStub {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub namespace: String,
+    pub name: String,
+    #[serde(rename = "ExternalID")]
+    pub external_id: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub topologies: Vec<CSITopology>,
+    pub access_mode: String,
+    pub attachment_mode: String,
+    pub current_readers: i64,
+    pub current_writers: i64,
+    pub schedulable: bool,
+    #[serde(rename = "PluginID")]
+    pub plugin_id: String,
+    pub provider: String,
+    pub controller_required: bool,
+    pub controllers_healthy: i64,
+    pub controllers_expected: i64,
+    pub nodes_healthy: i64,
+    pub nodes_expected: i64,
+    pub resource_exhausted: Option<DateTime<Utc>>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+// CSIVolumeRequest is the body used to register and create volumes
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSIVolumeRequest {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub volumes: Vec<CSIVolume>,
+}
+
+// CSISnapshot is a snapshot of a volume taken by its plugin
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSISnapshot {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "ExternalSourceVolumeID")]
+    pub external_source_volume_id: String,
+    pub size_bytes: i64,
+    pub create_time: i64,
+    pub is_ready: bool,
+    #[serde(rename = "SourceVolumeID")]
+    pub source_volume_id: String,
+    #[serde(rename = "PluginID")]
+    pub plugin_id: String,
+    pub name: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub secrets: CSISecrets,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub parameters: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSISnapshotRequest {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub snapshots: Vec<CSISnapshot>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSISnapshotListResponse {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub snapshots: Vec<CSISnapshot>,
+    pub next_token: String,
+}
+
+// CSIPlugin is a CSI plugin along with the controllers and nodes running it
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSIPlugin {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub provider: String,
+    pub version: String,
+    pub controller_required: bool,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub controllers: HashMap<String, CSIInfo>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub nodes: HashMap<String, CSIInfo>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub allocations: Vec<AllocationListStub>,
+    pub controllers_healthy: i64,
+    pub controllers_expected: i64,
+    pub nodes_healthy: i64,
+    pub nodes_expected: i64,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+impl CSIPlugin {
+    pub fn is_healthy(&self) -> bool {
+        self.controllers_healthy >= self.controllers_expected
+            && self.nodes_healthy >= self.nodes_expected
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CSIPluginListStub {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub provider: String,
+    pub controller_required: bool,
+    pub controllers_healthy: i64,
+    pub controllers_expected: i64,
+    pub nodes_healthy: i64,
+    pub nodes_expected: i64,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_volume() {
+        let js = r#"
+        {
+            "ID": "mysql",
+            "Name": "mysql",
+            "ExternalID": "vol-0123456789abcdef",
+            "Namespace": "default",
+            "Topologies": [{"Segments": {"topology.ebs.csi.aws.com/zone": "us-east-1a"}}],
+            "AccessMode": "single-node-writer",
+            "AttachmentMode": "file-system",
+            "MountOptions": {"FSType": "ext4", "MountFlags": null},
+            "Secrets": {"password": "hunter2"},
+            "Parameters": null,
+            "Capacity": 10737418240,
+            "RequestedCapabilities": [
+                {"AccessMode": "single-node-writer", "AttachmentMode": "file-system"}
+            ],
+            "ReadAllocs": {},
+            "WriteAllocs": {"a8198d79-cfdb-6593-a999-1e9adabcba2e": null},
+            "Allocations": [],
+            "Schedulable": true,
+            "PluginID": "aws-ebs0",
+            "Provider": "ebs.csi.aws.com",
+            "ControllerRequired": true,
+            "ControllersHealthy": 1,
+            "ControllersExpected": 1,
+            "NodesHealthy": 2,
+            "NodesExpected": 2,
+            "ResourceExhausted": null,
+            "CreateIndex": 42,
+            "ModifyIndex": 64
+        }
+        "#;
+        let volume: CSIVolume = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(volume.claims(), (0, 1));
+        assert_eq!(
+            volume.access_mode,
+            CSI_VOLUME_ACCESS_MODE_SINGLE_NODE_WRITER
+        );
+        assert_eq!(volume.secrets["password"].as_str(), "hunter2");
+        assert!(!format!("{:?}", volume).contains("hunter2"));
+    }
+}
@@ -6,7 +6,13 @@ use serde_with::serde_as;
 use std::collections::HashMap;
 use std::time::Duration;
 
-use super::serde_helpers::hashi_duration;
+use super::serde_helpers::nanos_duration;
+
+pub const DEPLOYMENT_STATUS_RUNNING: &str = "running";
+pub const DEPLOYMENT_STATUS_PAUSED: &str = "paused";
+pub const DEPLOYMENT_STATUS_FAILED: &str = "failed";
+pub const DEPLOYMENT_STATUS_SUCCESSFUL: &str = "successful";
+pub const DEPLOYMENT_STATUS_CANCELLED: &str = "cancelled";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -36,7 +42,7 @@ pub struct DeploymentState {
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub placed_canaries: Vec<String>,
     pub auto_revert: bool,
-    #[serde(deserialize_with = "hashi_duration::deserialize")]
+    #[serde(deserialize_with = "nanos_duration::deserialize")]
     pub progress_deadline: Option<Duration>, // FIXME: this is not optional in the original API but the deserializer expects it
     pub require_progress_by: DateTime<Utc>,
     pub promoted: bool,
@@ -46,3 +52,13 @@ pub struct DeploymentState {
     pub healthy_allocs: i64,
     pub unhealthy_allocs: i64,
 }
+
+impl Deployment {
+    // A deployment is terminal once it will make no further progress
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            DEPLOYMENT_STATUS_FAILED | DEPLOYMENT_STATUS_SUCCESSFUL | DEPLOYMENT_STATUS_CANCELLED
+        )
+    }
+}
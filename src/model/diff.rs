@@ -0,0 +1,493 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::jobs::Job;
+use super::tasks::{Task, TaskGroup};
+
+// Fields set by the server rather than the job's author
+const SERVER_FIELDS: &[&str] = &[
+    "ConsulToken",
+    "CreateIndex",
+    "Dispatched",
+    "JobModifyIndex",
+    "ModifyIndex",
+    "NomadTokenID",
+    "ParentID",
+    "Payload",
+    "Stable",
+    "Status",
+    "StatusDescription",
+    "SubmitTime",
+    "VaultToken",
+    "Version",
+];
+
+// Lists whose order carries no meaning, compared as sets of values
+const SETS: &[&str] = &[
+    "CanaryTags",
+    "Datacenters",
+    "MetaOptional",
+    "MetaRequired",
+    "Policies",
+    "Tags",
+];
+
+// Lists of objects identified by a field rather than their position
+const KEYED: &[(&str, &str)] = &[
+    ("Artifacts", "GetterSource"),
+    ("Checks", "Name"),
+    ("DynamicPorts", "Label"),
+    ("ReservedPorts", "Label"),
+    ("Services", "Name"),
+    ("Templates", "DestPath"),
+    ("VolumeMounts", "Destination"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiffType {
+    Added,
+    Deleted,
+    Edited,
+    None,
+}
+
+// FieldDiff is a changed value at a path such as `Update.MaxParallel`,
+// `Meta.team` or `Services[web].Tags`. Members added to or removed from a set
+// are reported one per FieldDiff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FieldDiff {
+    #[serde(rename = "Type")]
+    pub diff_type: DiffType,
+    pub name: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskDiff {
+    #[serde(rename = "Type")]
+    pub diff_type: DiffType,
+    pub name: String,
+    pub fields: Vec<FieldDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskGroupDiff {
+    #[serde(rename = "Type")]
+    pub diff_type: DiffType,
+    pub name: String,
+    pub fields: Vec<FieldDiff>,
+    pub tasks: Vec<TaskDiff>,
+}
+
+// JobDiff holds only what changed, groups and tasks are matched by name and
+// sorted by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobDiff {
+    #[serde(rename = "Type")]
+    pub diff_type: DiffType,
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub fields: Vec<FieldDiff>,
+    pub task_groups: Vec<TaskGroupDiff>,
+}
+
+impl JobDiff {
+    pub fn is_empty(&self) -> bool {
+        self.diff_type == DiffType::None
+    }
+}
+
+impl Job {
+    //
+    // Compare this job with a later version of it. Fields left unset on one
+    // side and empty on the other are equal, canonicalize both jobs first to
+    // also ignore the defaults applied by the server.
+    //
+    pub fn diff(&self, other: &Job) -> JobDiff {
+        let mut fields = Vec::new();
+        diff_object(
+            "",
+            &to_value(self),
+            &to_value(other),
+            &["TaskGroups"],
+            &mut fields,
+        );
+
+        let old = by_name(&self.task_groups, |g| &g.name);
+        let new = by_name(&other.task_groups, |g| &g.name);
+        let names: BTreeSet<&str> = old.keys().chain(new.keys()).copied().collect();
+        let task_groups: Vec<TaskGroupDiff> = names
+            .into_iter()
+            .filter_map(|name| diff_group(name, old.get(name).copied(), new.get(name).copied()))
+            .collect();
+
+        let diff_type = if fields.is_empty() && task_groups.is_empty() {
+            DiffType::None
+        } else {
+            DiffType::Edited
+        };
+        JobDiff {
+            diff_type,
+            id: other
+                .id
+                .clone()
+                .or_else(|| self.id.clone())
+                .unwrap_or_default(),
+            fields,
+            task_groups,
+        }
+    }
+}
+
+fn diff_group(
+    name: &str,
+    old: Option<&TaskGroup>,
+    new: Option<&TaskGroup>,
+) -> Option<TaskGroupDiff> {
+    let mut fields = Vec::new();
+    diff_object(
+        "",
+        &old.map(to_value).unwrap_or_default(),
+        &new.map(to_value).unwrap_or_default(),
+        &["Name", "Tasks"],
+        &mut fields,
+    );
+
+    let no_tasks = Vec::new();
+    let old_tasks = by_name(old.map_or(&no_tasks, |g| &g.tasks), |t| &t.name);
+    let new_tasks = by_name(new.map_or(&no_tasks, |g| &g.tasks), |t| &t.name);
+    let names: BTreeSet<&str> = old_tasks.keys().chain(new_tasks.keys()).copied().collect();
+    let tasks: Vec<TaskDiff> = names
+        .into_iter()
+        .filter_map(|name| {
+            diff_task(
+                name,
+                old_tasks.get(name).copied(),
+                new_tasks.get(name).copied(),
+            )
+        })
+        .collect();
+
+    let diff_type = change(
+        old.is_some(),
+        new.is_some(),
+        fields.is_empty() && tasks.is_empty(),
+    )?;
+    Some(TaskGroupDiff {
+        diff_type,
+        name: name.to_string(),
+        fields,
+        tasks,
+    })
+}
+
+fn diff_task(name: &str, old: Option<&Task>, new: Option<&Task>) -> Option<TaskDiff> {
+    let mut fields = Vec::new();
+    diff_object(
+        "",
+        &old.map(to_value).unwrap_or_default(),
+        &new.map(to_value).unwrap_or_default(),
+        &["Name"],
+        &mut fields,
+    );
+    let diff_type = change(old.is_some(), new.is_some(), fields.is_empty())?;
+    Some(TaskDiff {
+        diff_type,
+        name: name.to_string(),
+        fields,
+    })
+}
+
+// How an object present on either side changed, None when it did not
+fn change(old: bool, new: bool, unchanged: bool) -> Option<DiffType> {
+    match (old, new) {
+        (false, true) => Some(DiffType::Added),
+        (true, false) => Some(DiffType::Deleted),
+        _ if unchanged => None,
+        _ => Some(DiffType::Edited),
+    }
+}
+
+fn by_name<T>(items: &[T], name: impl Fn(&T) -> &String) -> BTreeMap<&str, &T> {
+    items
+        .iter()
+        .map(|item| (name(item).as_str(), item))
+        .collect()
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+fn diff_object(path: &str, old: &Value, new: &Value, skip: &[&str], out: &mut Vec<FieldDiff>) {
+    let empty = Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        if skip.contains(&key.as_str())
+            || (path.is_empty() && SERVER_FIELDS.contains(&key.as_str()))
+        {
+            continue;
+        }
+        let old = old.get(key).unwrap_or(&Value::Null);
+        let new = new.get(key).unwrap_or(&Value::Null);
+        diff_value(&join(path, key), key, old, new, out);
+    }
+}
+
+fn diff_value(path: &str, key: &str, old: &Value, new: &Value, out: &mut Vec<FieldDiff>) {
+    if is_empty(old) && is_empty(new) {
+        return;
+    }
+    match (shape(old, new), key) {
+        (Value::Object(_), _) => diff_object(path, old, new, &[], out),
+        (Value::Array(_), key) if SETS.contains(&key) => diff_set(path, old, new, out),
+        (Value::Array(_), key) => diff_array(path, key, old, new, out),
+        _ if old != new => out.push(field(path, old, new)),
+        _ => {}
+    }
+}
+
+// The kind of value being compared, taken from whichever side is present.
+// Values of different kinds are compared as scalars.
+fn shape<'a>(old: &'a Value, new: &'a Value) -> &'a Value {
+    match (old, new) {
+        (old, new) if is_empty(old) => new,
+        (old, new) if is_empty(new) => old,
+        (Value::Object(_), Value::Object(_)) | (Value::Array(_), Value::Array(_)) => old,
+        _ => &Value::Null,
+    }
+}
+
+fn diff_set(path: &str, old: &Value, new: &Value, out: &mut Vec<FieldDiff>) {
+    let old = members(old);
+    let new = members(new);
+    for removed in old.difference(&new) {
+        out.push(FieldDiff {
+            diff_type: DiffType::Deleted,
+            name: path.to_string(),
+            old: Some(removed.clone()),
+            new: None,
+        });
+    }
+    for added in new.difference(&old) {
+        out.push(FieldDiff {
+            diff_type: DiffType::Added,
+            name: path.to_string(),
+            old: None,
+            new: Some(added.clone()),
+        });
+    }
+}
+
+fn members(value: &Value) -> BTreeSet<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(scalar)
+        .collect()
+}
+
+// Lists of objects with a key field are matched on it when every element has
+// a distinct key, otherwise by position
+fn diff_array(path: &str, key: &str, old: &Value, new: &Value, out: &mut Vec<FieldDiff>) {
+    let no_items = Vec::new();
+    let old = old.as_array().unwrap_or(&no_items);
+    let new = new.as_array().unwrap_or(&no_items);
+
+    let id = KEYED.iter().find(|(k, _)| *k == key).map(|(_, id)| *id);
+    if let Some(id) = id {
+        if let (Some(old), Some(new)) = (keyed(old, id), keyed(new, id)) {
+            let ids: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for item in ids {
+                let old = old.get(item).copied().unwrap_or(&Value::Null);
+                let new = new.get(item).copied().unwrap_or(&Value::Null);
+                diff_value(&format!("{}[{}]", path, item), "", old, new, out);
+            }
+            return;
+        }
+    }
+
+    for i in 0..old.len().max(new.len()) {
+        let old = old.get(i).unwrap_or(&Value::Null);
+        let new = new.get(i).unwrap_or(&Value::Null);
+        diff_value(&format!("{}[{}]", path, i), "", old, new, out);
+    }
+}
+
+fn keyed<'a>(items: &'a [Value], id: &str) -> Option<BTreeMap<String, &'a Value>> {
+    let mut keyed = BTreeMap::new();
+    for item in items {
+        let key = item.get(id).and_then(scalar).filter(|k| !k.is_empty())?;
+        if keyed.insert(key, item).is_some() {
+            return None;
+        }
+    }
+    Some(keyed)
+}
+
+fn field(path: &str, old: &Value, new: &Value) -> FieldDiff {
+    let diff_type = match (is_empty(old), is_empty(new)) {
+        (true, _) => DiffType::Added,
+        (_, true) => DiffType::Deleted,
+        _ => DiffType::Edited,
+    };
+    FieldDiff {
+        diff_type,
+        name: path.to_string(),
+        old: render(old),
+        new: render(new),
+    }
+}
+
+fn render(value: &Value) -> Option<String> {
+    if is_empty(value) {
+        None
+    } else {
+        Some(scalar(value).unwrap_or_else(|| value.to_string()))
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+// Unset and empty values are equal so Option and Vec fields compare the same
+// whether they were left out or sent empty
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::resources::{NetworkResource, Resources};
+    use crate::model::services::Service;
+
+    fn job(datacenters: &[&str], image: &str, tags: &[&str], cache: bool) -> Job {
+        let service = tags
+            .iter()
+            .fold(Service::new("web").with_port("http"), |s, t| s.with_tag(t));
+        let mut job = Job::service("web")
+            .datacenters(datacenters.iter().copied())
+            .meta("team", "platform")
+            .group(
+                TaskGroup::builder("web")
+                    .network(NetworkResource::bridge().with_port("http"))
+                    .service(service)
+                    .task(
+                        Task::builder("server", "docker")
+                            .config("image", image)
+                            .resources(Resources::new(100, 128)),
+                    ),
+            );
+        if cache {
+            job = job.group(TaskGroup::builder("cache").task(Task::builder("redis", "docker")));
+        }
+        job.build().expect("build")
+    }
+
+    #[test]
+    fn diff_jobs() {
+        let old = job(&["dc1", "dc2"], "web:1.0", &["v1", "http"], true);
+        assert!(old.diff(&old.clone()).is_empty());
+
+        let mut new = job(&["dc2", "dc1", "dc3"], "web:1.1", &["http", "v2"], false);
+        new.meta.insert("team".to_string(), "infra".to_string());
+        new.version = Some(4);
+        let mut reordered = new.task_groups[0].clone();
+        reordered.name = "api".to_string();
+        new.task_groups.insert(0, reordered);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.diff_type, DiffType::Edited);
+        assert_eq!(
+            diff.fields,
+            vec![
+                FieldDiff {
+                    diff_type: DiffType::Added,
+                    name: "Datacenters".to_string(),
+                    old: None,
+                    new: Some("dc3".to_string()),
+                },
+                FieldDiff {
+                    diff_type: DiffType::Edited,
+                    name: "Meta.team".to_string(),
+                    old: Some("platform".to_string()),
+                    new: Some("infra".to_string()),
+                },
+            ]
+        );
+
+        let groups: Vec<(&str, DiffType)> = diff
+            .task_groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.diff_type))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("api", DiffType::Added),
+                ("cache", DiffType::Deleted),
+                ("web", DiffType::Edited),
+            ]
+        );
+
+        let web = &diff.task_groups[2];
+        let changes: Vec<(&str, DiffType, Option<&str>)> = web
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    f.name.as_str(),
+                    f.diff_type,
+                    f.new.as_deref().or(f.old.as_deref()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("Services[web].Tags", DiffType::Deleted, Some("v1")),
+                ("Services[web].Tags", DiffType::Added, Some("v2")),
+            ]
+        );
+        assert_eq!(web.tasks.len(), 1);
+        assert_eq!(
+            web.tasks[0].fields,
+            vec![FieldDiff {
+                diff_type: DiffType::Edited,
+                name: "Config.image".to_string(),
+                old: Some("web:1.0".to_string()),
+                new: Some("web:1.1".to_string()),
+            }]
+        );
+    }
+}
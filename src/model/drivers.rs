@@ -0,0 +1,385 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+use super::secret::Secret;
+use super::serde_helpers::single_block;
+use super::tasks::Task;
+use crate::error::Result;
+
+pub const DRIVER_DOCKER: &str = "docker";
+pub const DRIVER_EXEC: &str = "exec";
+pub const DRIVER_RAW_EXEC: &str = "raw_exec";
+pub const DRIVER_JAVA: &str = "java";
+pub const DRIVER_QEMU: &str = "qemu";
+
+//
+// DriverConfig is the typed form of `Task::config` for the drivers built into
+// Nomad, chosen by `Task::driver`. Other drivers use the raw map as `Custom`.
+// Options not modelled by a typed config are kept in its `extra` map so
+// converting to and from the raw map loses nothing.
+//
+#[derive(Debug, Clone)]
+pub enum DriverConfig {
+    Docker(Box<DockerConfig>),
+    Exec(ExecConfig),
+    RawExec(RawExecConfig),
+    Java(JavaConfig),
+    Qemu(QemuConfig),
+    Custom(HashMap<String, Value>),
+}
+
+impl DriverConfig {
+    // Parse the raw config of a task run by `driver`
+    pub fn from_map(driver: &str, config: &HashMap<String, Value>) -> Result<Self> {
+        let value = || Value::Object(config.clone().into_iter().collect());
+        Ok(match driver {
+            DRIVER_DOCKER => DriverConfig::Docker(Box::new(serde_json::from_value(value())?)),
+            DRIVER_EXEC => DriverConfig::Exec(serde_json::from_value(value())?),
+            DRIVER_RAW_EXEC => DriverConfig::RawExec(serde_json::from_value(value())?),
+            DRIVER_JAVA => DriverConfig::Java(serde_json::from_value(value())?),
+            DRIVER_QEMU => DriverConfig::Qemu(serde_json::from_value(value())?),
+            _ => DriverConfig::Custom(config.clone()),
+        })
+    }
+
+    // The driver running the config, None for `Custom`
+    pub fn driver(&self) -> Option<&'static str> {
+        match self {
+            DriverConfig::Docker(_) => Some(DRIVER_DOCKER),
+            DriverConfig::Exec(_) => Some(DRIVER_EXEC),
+            DriverConfig::RawExec(_) => Some(DRIVER_RAW_EXEC),
+            DriverConfig::Java(_) => Some(DRIVER_JAVA),
+            DriverConfig::Qemu(_) => Some(DRIVER_QEMU),
+            DriverConfig::Custom(_) => None,
+        }
+    }
+
+    pub fn to_map(&self) -> HashMap<String, Value> {
+        match self {
+            DriverConfig::Docker(config) => to_map(config),
+            DriverConfig::Exec(config) => to_map(config),
+            DriverConfig::RawExec(config) => to_map(config),
+            DriverConfig::Java(config) => to_map(config),
+            DriverConfig::Qemu(config) => to_map(config),
+            DriverConfig::Custom(config) => config.clone(),
+        }
+    }
+}
+
+// The typed configs only have string keys so always serialize to an object
+fn to_map<T: Serialize>(config: &T) -> HashMap<String, Value> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(map)) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    }
+}
+
+impl From<DockerConfig> for DriverConfig {
+    fn from(config: DockerConfig) -> Self {
+        DriverConfig::Docker(Box::new(config))
+    }
+}
+
+impl From<ExecConfig> for DriverConfig {
+    fn from(config: ExecConfig) -> Self {
+        DriverConfig::Exec(config)
+    }
+}
+
+impl From<RawExecConfig> for DriverConfig {
+    fn from(config: RawExecConfig) -> Self {
+        DriverConfig::RawExec(config)
+    }
+}
+
+impl From<JavaConfig> for DriverConfig {
+    fn from(config: JavaConfig) -> Self {
+        DriverConfig::Java(config)
+    }
+}
+
+impl From<QemuConfig> for DriverConfig {
+    fn from(config: QemuConfig) -> Self {
+        DriverConfig::Qemu(config)
+    }
+}
+
+impl Task {
+    pub fn driver_config(&self) -> Result<DriverConfig> {
+        DriverConfig::from_map(self.driver.as_deref().unwrap_or_default(), &self.config)
+    }
+
+    // Replace the config, switching the task to the config's driver
+    pub fn set_driver_config(&mut self, config: DriverConfig) {
+        if let Some(driver) = config.driver() {
+            self.driver = Some(driver.to_string());
+        }
+        self.config = config.to_map();
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockerConfig {
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entrypoint: Vec<String>,
+    // Labels of the group network ports to publish
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_pull: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<bool>,
+    // Bind mounts written as "host_path:container_path[:ro]"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_driver: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mount: Vec<DockerMount>,
+    #[serde(
+        deserialize_with = "single_block::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub auth: Option<DockerAuth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_soft_fail: Option<bool>,
+    #[serde(
+        deserialize_with = "single_block::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub logging: Option<DockerLogging>,
+    #[serde(
+        deserialize_with = "single_block::deserialize",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub labels: HashMap<String, String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl DockerConfig {
+    pub fn new(image: &str) -> Self {
+        DockerConfig {
+            image: image.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockerMount {
+    // One of "bind", "volume" or "tmpfs"
+    #[serde(rename = "type")]
+    pub mount_type: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readonly: Option<bool>,
+    // bind_options, volume_options and tmpfs_options
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockerAuth {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<Secret<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_address: Option<String>,
+    // Read credentials from a helper instead, such as "ecr-login"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub helper: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockerLogging {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub log_type: Option<String>,
+    #[serde(
+        deserialize_with = "single_block::deserialize",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub config: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecConfig {
+    pub command: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // "private" or "host"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipc_mode: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cap_add: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cap_drop: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ExecConfig {
+    pub fn new(command: &str) -> Self {
+        ExecConfig {
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RawExecConfig {
+    pub command: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl RawExecConfig {
+    pub fn new(command: &str) -> Self {
+        RawExecConfig {
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JavaConfig {
+    // Either a jar or a class to run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jar_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_path: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub jvm_options: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QemuConfig {
+    pub image_path: String,
+    // "tcg" or "kvm"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accelerator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graceful_shutdown: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guest_agent: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl QemuConfig {
+    pub fn new(image_path: &str) -> Self {
+        QemuConfig {
+            image_path: image_path.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn raw(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).expect("raw config")
+    }
+
+    #[test]
+    fn docker_config_round_trip() {
+        let config = raw(json!({
+            "image": "redis:7",
+            "ports": ["db"],
+            "auth": [{"username": "ci", "password": "hunter2"}],
+            "labels": [{"team": "platform"}],
+            "logging": {"type": "syslog", "config": [{"tag": "redis"}]},
+            "mount": [{"type": "tmpfs", "target": "/scratch", "tmpfs_options": {"size": 1024}}],
+            "shm_size": 256
+        }));
+        let docker = match DriverConfig::from_map(DRIVER_DOCKER, &config).expect("parse") {
+            DriverConfig::Docker(docker) => docker,
+            other => panic!("expected docker config, got {:?}", other),
+        };
+        assert_eq!(docker.image, "redis:7");
+        assert_eq!(
+            docker.auth.as_ref().unwrap().username.as_deref(),
+            Some("ci")
+        );
+        assert!(!format!("{:?}", docker).contains("hunter2"));
+        assert_eq!(docker.labels["team"], "platform");
+        assert_eq!(docker.logging.as_ref().unwrap().config["tag"], "redis");
+        assert_eq!(docker.mount[0].mount_type, "tmpfs");
+        assert_eq!(docker.extra["shm_size"], json!(256));
+
+        let map = DriverConfig::Docker(docker).to_map();
+        assert_eq!(
+            map["auth"],
+            json!({"username": "ci", "password": "hunter2"})
+        );
+        assert_eq!(map["labels"], json!({"team": "platform"}));
+        assert_eq!(map["mount"][0]["tmpfs_options"], json!({"size": 1024}));
+        assert_eq!(map["shm_size"], json!(256));
+        assert!(!map.contains_key("command"));
+    }
+
+    #[test]
+    fn task_driver_config() {
+        let mut task = Task::builder("backup", "raw_exec").build().expect("task");
+        task.set_driver_config(
+            ExecConfig {
+                args: vec!["--all".to_string()],
+                ..ExecConfig::new("/usr/bin/backup")
+            }
+            .into(),
+        );
+        assert_eq!(task.driver.as_deref(), Some("exec"));
+        assert_eq!(task.config["command"], json!("/usr/bin/backup"));
+        assert!(matches!(task.driver_config(), Ok(DriverConfig::Exec(c)) if c.args == ["--all"]));
+
+        task.driver = Some("podman".to_string());
+        assert!(matches!(task.driver_config(), Ok(DriverConfig::Custom(c)) if c.len() == 2));
+
+        task.driver = Some("docker".to_string());
+        task.config = raw(json!({"image": ["not", "a", "string"]}));
+        assert!(task.driver_config().is_err());
+    }
+}
@@ -7,7 +7,13 @@ use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
 use super::allocations::AllocationMetric;
-use super::serde_helpers::hashi_duration;
+use super::serde_helpers::nanos_duration;
+
+pub const EVAL_STATUS_BLOCKED: &str = "blocked";
+pub const EVAL_STATUS_PENDING: &str = "pending";
+pub const EVAL_STATUS_COMPLETE: &str = "complete";
+pub const EVAL_STATUS_FAILED: &str = "failed";
+pub const EVAL_STATUS_CANCELED: &str = "canceled";
 
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,7 +36,7 @@ pub struct Evaluation {
     pub deployment_id: String,
     pub status: String,
     pub status_description: String,
-    #[serde(deserialize_with = "hashi_duration::deserialize")]
+    #[serde(deserialize_with = "nanos_duration::deserialize")]
     pub wait: Option<Duration>, // FIXME: deserializer expects Option type but this field might not be optional
     pub wait_until: DateTime<Utc>,
     pub next_eval: String,
@@ -55,7 +61,18 @@ pub struct Evaluation {
     pub modify_time: SystemTime,
 }
 
+impl Evaluation {
+    // An evaluation is terminal once the scheduler will no longer act on it
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            EVAL_STATUS_COMPLETE | EVAL_STATUS_FAILED | EVAL_STATUS_CANCELED
+        )
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use super::*;
 
@@ -102,6 +119,7 @@ mod tests {
             evaluation.deployment_id,
             "98605b0e-87da-e425-14e2-31d0c38cf06a"
         );
+        assert!(evaluation.is_terminal());
         println!("evaluation = {:?}", evaluation);
     }
 }
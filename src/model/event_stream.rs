@@ -17,6 +17,7 @@ pub enum Topic {
     All,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventPayload {
     Allocation(Allocation),
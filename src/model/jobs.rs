@@ -4,8 +4,10 @@ use serde_with::rust::default_on_null;
 use std::collections::HashMap;
 use std::time::Duration;
 
+use super::builder::JobBuilder;
 use super::constraint::Constraint;
-use super::serde_helpers::hashi_duration;
+use super::secret::Secret;
+use super::serde_helpers::{base64_bytes, nanos_duration};
 use super::tasks::{Affinity, MigrateStrategy, ReschedulePolicy, Spread, TaskGroup};
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -16,17 +18,20 @@ pub enum JobType {
     System,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for JobType {
     fn default() -> JobType {
         JobType::Service
     }
 }
 
-#[allow(dead_code)]
 pub const DEFAULT_NAMESPACE: &str = "default";
-#[allow(dead_code)]
 pub const GLOBAL_REGION: &str = "global";
 
+pub const DISPATCH_PAYLOAD_FORBIDDEN: &str = "forbidden";
+pub const DISPATCH_PAYLOAD_OPTIONAL: &str = "optional";
+pub const DISPATCH_PAYLOAD_REQUIRED: &str = "required";
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct MultiregionStrategy {
@@ -69,15 +74,15 @@ pub struct PeriodicConfig {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct UpdateStrategy {
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub stagger: Option<Duration>,
     pub max_parallel: Option<i64>,
     pub health_check: Option<String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub min_healthy_time: Option<Duration>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub healthy_deadline: Option<Duration>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub progress_deadline: Option<Duration>,
     pub canary: Option<i64>,
     pub auto_revert: Option<bool>,
@@ -94,6 +99,83 @@ pub struct ParameterizedJobConfig {
     pub meta_optional: Vec<String>,
 }
 
+impl Job {
+    // Start building a long running service job, see `JobBuilder`
+    pub fn service(id: &str) -> JobBuilder {
+        JobBuilder::new(id, JobType::Service)
+    }
+
+    pub fn batch(id: &str) -> JobBuilder {
+        JobBuilder::new(id, JobType::Batch)
+    }
+
+    pub fn system(id: &str) -> JobBuilder {
+        JobBuilder::new(id, JobType::System)
+    }
+
+    // The namespace the job is registered in, the default if unset
+    pub fn namespace_or_default(&self) -> &str {
+        self.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE)
+    }
+}
+
+impl ParameterizedJobConfig {
+    //
+    // Check a dispatch payload and meta against the requirements of the
+    // parameterized job, describing every problem found
+    //
+    pub fn check_dispatch(
+        &self,
+        payload: &[u8],
+        meta: &HashMap<String, String>,
+    ) -> Result<(), String> {
+        let mut problems = Vec::new();
+
+        match self.payload.as_deref().unwrap_or(DISPATCH_PAYLOAD_OPTIONAL) {
+            DISPATCH_PAYLOAD_REQUIRED if payload.is_empty() => {
+                problems.push("payload is required".to_string())
+            }
+            DISPATCH_PAYLOAD_FORBIDDEN if !payload.is_empty() => {
+                problems.push("payload is forbidden".to_string())
+            }
+            _ => {}
+        }
+
+        let mut missing: Vec<&str> = self
+            .meta_required
+            .iter()
+            .filter(|key| !meta.contains_key(*key))
+            .map(String::as_str)
+            .collect();
+        missing.sort_unstable();
+        if !missing.is_empty() {
+            problems.push(format!(
+                "missing required meta keys: {}",
+                missing.join(", ")
+            ));
+        }
+
+        let mut unexpected: Vec<&str> = meta
+            .keys()
+            .filter(|key| !self.meta_required.contains(key) && !self.meta_optional.contains(key))
+            .map(String::as_str)
+            .collect();
+        unexpected.sort_unstable();
+        if !unexpected.is_empty() {
+            problems.push(format!(
+                "meta keys not permitted by the job: {}",
+                unexpected.join(", ")
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct Job {
@@ -110,11 +192,14 @@ pub struct Job {
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub datacenters: Vec<String>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub constriants: Vec<Constraint>,
+    pub constraints: Vec<Constraint>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub affinities: Vec<Affinity>,
-    #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub task_group: Vec<TaskGroup>,
+    #[serde(
+        rename = "TaskGroups",
+        deserialize_with = "default_on_null::deserialize"
+    )]
+    pub task_groups: Vec<TaskGroup>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub update: Option<UpdateStrategy>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
@@ -131,8 +216,8 @@ pub struct Job {
     pub migrate: Option<MigrateStrategy>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub meta: HashMap<String, String>,
-    pub consul_token: Option<String>,
-    pub vault_token: Option<String>,
+    pub consul_token: Option<Secret<String>>,
+    pub vault_token: Option<Secret<String>>,
 
     // Server managed fields
     #[serde(deserialize_with = "default_on_null::deserialize")]
@@ -140,10 +225,10 @@ pub struct Job {
     pub parent_id: Option<String>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub dispatched: bool,
-    #[serde(deserialize_with = "default_on_null::deserialize")]
+    #[serde(with = "base64_bytes")]
     pub payload: Vec<u8>,
     pub vault_namespace: Option<String>,
-    pub nomad_token_id: Option<String>,
+    pub nomad_token_id: Option<Secret<String>>,
     pub status: Option<String>,
     pub status_description: Option<String>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
@@ -219,13 +304,115 @@ pub struct JobListStub {
     pub submit_time: i64,
 }
 
+impl JobListStub {
+    // The namespace the job is registered in, the default if unset
+    pub fn namespace_or_default(&self) -> &str {
+        self.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct JobSpec {
     pub job: Job,
 }
 
+// JobsParseRequest converts an HCL job specification into a job. Variables
+// are given in the format of a `.nomadvars` file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobsParseRequest {
+    #[serde(rename = "JobHCL")]
+    pub job_hcl: String,
+    #[serde(rename = "HCLv1")]
+    pub hcl_v1: bool,
+    pub variables: String,
+    pub canonicalize: bool,
+}
+
+// JobRegisterRequest is used to register or update a job
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobRegisterRequest {
+    pub job: Job,
+    pub enforce_index: bool,
+    pub job_modify_index: u64,
+    pub policy_override: bool,
+    pub preserve_counts: bool,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct JobRegisterResponse {
+    #[serde(rename = "EvalID")]
+    pub eval_id: String,
+    pub eval_create_index: u64,
+    pub job_modify_index: u64,
+    pub warnings: String,
+}
+
+// JobRevertRequest reverts a job to a prior version
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobRevertRequest {
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    pub job_version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforce_prior_version: Option<u64>,
+}
+
+// JobStabilityRequest marks a job version as stable or unstable
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobStabilityRequest {
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    pub job_version: u64,
+    pub stable: bool,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct JobStabilityResponse {
+    pub job_modify_index: u64,
+    pub index: u64,
+}
+
+// JobDispatchRequest creates an instance of a parameterized job
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobDispatchRequest {
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    #[serde(with = "base64_bytes")]
+    pub payload: Vec<u8>,
+    pub meta: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct JobDispatchResponse {
+    #[serde(rename = "DispatchedJobID")]
+    pub dispatched_job_id: String,
+    #[serde(rename = "EvalID")]
+    pub eval_id: String,
+    pub eval_create_index: u64,
+    pub job_create_index: u64,
+    pub index: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct PeriodicForceResponse {
+    #[serde(rename = "EvalID")]
+    pub eval_id: String,
+    pub eval_create_index: u64,
+    pub index: u64,
+}
+
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use super::*;
 
@@ -396,6 +583,93 @@ mod tests {
         }
         "#;
 
-        let _job_spec: JobSpec = serde_json::from_str(&js).expect("deserialize failed");
+        let job_spec: JobSpec = serde_json::from_str(&js).expect("deserialize failed");
+        assert_eq!(job_spec.job.task_groups.len(), 1);
+        assert_eq!(job_spec.job.task_groups[0].tasks[0].name, "redis");
+    }
+
+    #[test]
+    fn check_dispatch() {
+        let config = ParameterizedJobConfig {
+            payload: Some(DISPATCH_PAYLOAD_REQUIRED.to_string()),
+            meta_required: vec!["input".to_string()],
+            meta_optional: vec!["priority".to_string()],
+        };
+
+        let mut meta = HashMap::new();
+        meta.insert("input".to_string(), "s3://bucket/key".to_string());
+        assert!(config.check_dispatch(b"data", &meta).is_ok());
+
+        meta.remove("input");
+        meta.insert("colour".to_string(), "blue".to_string());
+        assert_eq!(
+            config.check_dispatch(b"", &meta),
+            Err("payload is required; missing required meta keys: input; meta keys not permitted by the job: colour".to_string())
+        );
+
+        let forbidden = ParameterizedJobConfig {
+            payload: Some(DISPATCH_PAYLOAD_FORBIDDEN.to_string()),
+            ..Default::default()
+        };
+        assert!(forbidden.check_dispatch(b"data", &HashMap::new()).is_err());
+        assert!(forbidden.check_dispatch(b"", &HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn deserialize_dispatch_response() {
+        let js = r#"
+        {
+            "Index": 13,
+            "JobCreateIndex": 12,
+            "EvalCreateIndex": 13,
+            "EvalID": "e5f55fac-bc69-119d-528a-1fc7ade5e02c",
+            "DispatchedJobID": "example/dispatch-1485408778-81644024"
+        }
+        "#;
+        let response: JobDispatchResponse = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(
+            response.dispatched_job_id,
+            "example/dispatch-1485408778-81644024"
+        );
+        assert_eq!(response.eval_id, "e5f55fac-bc69-119d-528a-1fc7ade5e02c");
+    }
+
+    #[test]
+    fn debug_redacts_tokens() {
+        let js = r#"
+        {
+            "ID": "example",
+            "ConsulToken": "consul-secret",
+            "VaultToken": "vault-secret",
+            "TaskGroups": [
+                {
+                    "Name": "cache",
+                    "Tasks": [
+                        {
+                            "Name": "redis",
+                            "Env": {"REDIS_PASSWORD": "hunter2"},
+                            "Templates": [{"EmbeddedTmpl": "password={{ key \"pw\" }}"}]
+                        }
+                    ]
+                }
+            ]
+        }
+        "#;
+        let job: Job = serde_json::from_str(js).expect("deserialize failed");
+        let debug = format!("{:#?}", job);
+        for secret in &["consul-secret", "vault-secret", "hunter2", "password="] {
+            assert!(!debug.contains(secret), "{} leaked", secret);
+        }
+        assert!(debug.contains("REDIS_PASSWORD"));
+        assert_eq!(
+            job.vault_token.as_deref().map(String::as_str),
+            Some("vault-secret")
+        );
+
+        let js = serde_json::to_value(&job).expect("serialize failed");
+        assert_eq!(
+            js["TaskGroups"][0]["Tasks"][0]["Env"]["REDIS_PASSWORD"],
+            "hunter2"
+        );
     }
 }
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
+
+use std::collections::HashMap;
+
+// Used as the namespace of a list query to return results from every
+// namespace the token has access to
+pub const ALL_NAMESPACES: &str = "*";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct NamespaceCapabilities {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub enabled_task_drivers: Vec<String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub disabled_task_drivers: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Namespace {
+    pub name: String,
+    pub description: String,
+    pub quota: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub capabilities: Option<NamespaceCapabilities>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub meta: HashMap<String, String>,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_namespace() {
+        let js = r#"
+        {
+            "Capabilities": {
+                "DisabledTaskDrivers": ["raw_exec"],
+                "EnabledTaskDrivers": null
+            },
+            "CreateIndex": 31,
+            "Description": "Production API Servers",
+            "Meta": {
+                "contact": "platform@example.com"
+            },
+            "ModifyIndex": 31,
+            "Name": "api-prod",
+            "Quota": ""
+        }
+        "#;
+
+        let ns: Namespace = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(ns.name, "api-prod");
+        assert_eq!(ns.meta["contact"], "platform@example.com");
+        let capabilities = ns.capabilities.expect("capabilities");
+        assert_eq!(capabilities.disabled_task_drivers, vec!["raw_exec"]);
+        assert!(capabilities.enabled_task_drivers.is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
+
+use std::time::Duration;
+
+use super::serde_helpers::readable_duration;
+
+pub const SCHEDULER_ALGORITHM_BINPACK: &str = "binpack";
+pub const SCHEDULER_ALGORITHM_SPREAD: &str = "spread";
+
+// RaftServer is a server in the raft configuration
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct RaftServer {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub node: String,
+    pub address: String,
+    pub leader: bool,
+    pub voter: bool,
+    pub raft_protocol: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct RaftConfiguration {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub servers: Vec<RaftServer>,
+    pub index: u64,
+}
+
+impl RaftConfiguration {
+    pub fn leader(&self) -> Option<&RaftServer> {
+        self.servers.iter().find(|s| s.leader)
+    }
+}
+
+// AutopilotConfiguration controls the automatic management of raft peers
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotConfiguration {
+    pub cleanup_dead_servers: bool,
+    #[serde(with = "readable_duration")]
+    pub last_contact_threshold: Duration,
+    pub max_trailing_logs: u64,
+    pub min_quorum: u32,
+    #[serde(with = "readable_duration")]
+    pub server_stabilization_time: Duration,
+    pub enable_redundancy_zones: bool,
+    pub disable_upgrade_migration: bool,
+    pub enable_custom_upgrades: bool,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+// ServerHealth is the autopilot view of a single server
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServerHealth {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub name: String,
+    pub address: String,
+    pub serf_status: String,
+    pub version: String,
+    pub leader: bool,
+    #[serde(with = "readable_duration")]
+    pub last_contact: Duration,
+    pub last_term: u64,
+    pub last_index: u64,
+    pub healthy: bool,
+    pub voter: bool,
+    pub stable_since: Option<DateTime<Utc>>,
+}
+
+// OperatorHealthReply is the health of the servers as seen by autopilot
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct OperatorHealthReply {
+    pub healthy: bool,
+    pub failure_tolerance: i32,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub servers: Vec<ServerHealth>,
+    pub leader: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub voters: Vec<String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub non_voters: Vec<String>,
+}
+
+impl OperatorHealthReply {
+    pub fn unhealthy_servers(&self) -> impl Iterator<Item = &ServerHealth> {
+        self.servers.iter().filter(|s| !s.healthy)
+    }
+}
+
+// PreemptionConfig enables preemption per scheduler type
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct PreemptionConfig {
+    pub system_scheduler_enabled: bool,
+    pub sys_batch_scheduler_enabled: bool,
+    pub batch_scheduler_enabled: bool,
+    pub service_scheduler_enabled: bool,
+}
+
+// SchedulerConfiguration is the cluster wide configuration of the scheduler
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SchedulerConfiguration {
+    pub scheduler_algorithm: String,
+    pub preemption_config: PreemptionConfig,
+    pub memory_oversubscription_enabled: bool,
+    pub reject_job_registration: bool,
+    pub pause_eval_broker: bool,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SchedulerConfigurationResponse {
+    pub scheduler_config: Option<SchedulerConfiguration>,
+}
+
+// SchedulerSetConfigurationResponse reports whether a (CAS) update was applied
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SchedulerSetConfigurationResponse {
+    pub updated: bool,
+    pub index: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_autopilot_configuration() {
+        let js = r#"
+        {
+            "CleanupDeadServers": true,
+            "LastContactThreshold": "200ms",
+            "MaxTrailingLogs": 250,
+            "MinQuorum": 3,
+            "ServerStabilizationTime": "10s",
+            "EnableRedundancyZones": false,
+            "DisableUpgradeMigration": false,
+            "EnableCustomUpgrades": false,
+            "CreateIndex": 4,
+            "ModifyIndex": 4
+        }
+        "#;
+        let config: AutopilotConfiguration = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(config.last_contact_threshold, Duration::from_millis(200));
+        assert_eq!(config.server_stabilization_time, Duration::from_secs(10));
+
+        let js = serde_json::to_value(&config).expect("serialize failed");
+        assert_eq!(js["LastContactThreshold"], "200ms");
+    }
+
+    #[test]
+    fn deserialize_scheduler_configuration() {
+        let js = r#"
+        {
+            "SchedulerConfig": {
+                "SchedulerAlgorithm": "spread",
+                "MemoryOversubscriptionEnabled": true,
+                "PreemptionConfig": {
+                    "SystemSchedulerEnabled": true,
+                    "SysBatchSchedulerEnabled": false,
+                    "BatchSchedulerEnabled": false,
+                    "ServiceSchedulerEnabled": false
+                },
+                "CreateIndex": 5,
+                "ModifyIndex": 5
+            },
+            "Index": 5,
+            "LastContact": 0,
+            "KnownLeader": true
+        }
+        "#;
+        let response: SchedulerConfigurationResponse =
+            serde_json::from_str(js).expect("deserialize failed");
+        let config = response.scheduler_config.expect("config");
+        assert_eq!(config.scheduler_algorithm, SCHEDULER_ALGORITHM_SPREAD);
+        assert!(config.preemption_config.system_scheduler_enabled);
+        assert!(config.memory_oversubscription_enabled);
+    }
+}
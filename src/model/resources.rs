@@ -22,6 +22,16 @@ pub struct Resources {
     // NOTE: Deprecated fields omitted
 }
 
+impl Resources {
+    pub fn new(cpu: i64, memory_mb: i64) -> Self {
+        Resources {
+            cpu: Some(cpu),
+            memory_mb: Some(memory_mb),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct Port {
@@ -60,6 +70,53 @@ pub struct NetworkResource {
     // NOTE: Deprecated fields omitted
 }
 
+impl NetworkResource {
+    // A group network in its own namespace, needed for Consul Connect
+    pub fn bridge() -> Self {
+        NetworkResource {
+            mode: Some("bridge".to_string()),
+            ..Default::default()
+        }
+    }
+
+    // A group network sharing the host's namespace
+    pub fn host() -> Self {
+        NetworkResource {
+            mode: Some("host".to_string()),
+            ..Default::default()
+        }
+    }
+
+    // A port allocated dynamically by the scheduler
+    pub fn with_port(self, label: &str) -> Self {
+        self.with_dynamic_port(label, None)
+    }
+
+    // A dynamic port mapped to `to` inside the network namespace
+    pub fn with_mapped_port(self, label: &str, to: i64) -> Self {
+        self.with_dynamic_port(label, Some(to))
+    }
+
+    // A port reserved at the same number on every node
+    pub fn with_static_port(mut self, label: &str, value: i64) -> Self {
+        self.reserved_ports.push(Port {
+            label: Some(label.to_string()),
+            value: Some(value),
+            ..Default::default()
+        });
+        self
+    }
+
+    fn with_dynamic_port(mut self, label: &str, to: Option<i64>) -> Self {
+        self.dynamic_ports.push(Port {
+            label: Some(label.to_string()),
+            to,
+            ..Default::default()
+        });
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct RequestedDevice {
@@ -118,6 +175,7 @@ pub struct Attribute {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use super::*;
 
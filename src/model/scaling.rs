@@ -1,20 +1,206 @@
 use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const SCALING_POLICY_TYPE_HORIZONTAL: &str = "horizontal";
+
+// ScalingTarget identifies the thing being scaled. Policies for job task
+// groups use the well known keys; other targets (for example cloud instance
+// groups used by the autoscaler) keep their keys in `other`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
+pub struct ScalingTarget {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    #[serde(flatten)]
+    pub other: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
 pub struct ScalingPolicy {
     pub min: Option<i64>,
     pub max: Option<i64>,
-    pub policy: HashMap<String, String>, // FIXME: what are these
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub policy: HashMap<String, serde_json::Value>,
     pub enabled: Option<bool>,
     #[serde(rename = "Type")]
     pub policy_type: Option<String>,
 
     // Server managed fields
+    #[serde(rename = "ID")]
     pub id: String,
     pub namespace: String,
-    pub target: HashMap<String, String>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub target: ScalingTarget,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+// ScalingPolicyListStub is returned when listing scaling policies
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ScalingPolicyListStub {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub enabled: bool,
+    #[serde(rename = "Type")]
+    pub policy_type: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub target: ScalingTarget,
     pub create_index: u64,
     pub modify_index: u64,
 }
+
+// ScalingRequest changes the count of a task group, or records a scaling
+// event without changing the count when `count` is `None`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ScalingRequest {
+    pub count: Option<i64>,
+    pub target: ScalingTarget,
+    pub message: String,
+    pub error: bool,
+    pub meta: HashMap<String, serde_json::Value>,
+    pub policy_override: bool,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ScalingEvent {
+    pub count: Option<i64>,
+    pub previous_count: i64,
+    pub error: bool,
+    pub message: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub meta: HashMap<String, serde_json::Value>,
+    #[serde(rename = "EvalID")]
+    pub eval_id: Option<String>,
+    pub time: u64,
+    pub create_index: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct TaskGroupScaleStatus {
+    pub desired: i64,
+    pub placed: i64,
+    pub running: i64,
+    pub healthy: i64,
+    pub unhealthy: i64,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub events: Vec<ScalingEvent>,
+}
+
+// JobScaleStatusResponse is the scaling status of each group of a job
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct JobScaleStatusResponse {
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    pub namespace: String,
+    pub job_create_index: u64,
+    pub job_modify_index: u64,
+    pub job_stopped: bool,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub task_groups: HashMap<String, TaskGroupScaleStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_scaling_policy() {
+        let js = r#"
+        {
+            "CreateIndex": 10,
+            "Enabled": true,
+            "ID": "5e9772e6-6f37-4b66-a0f9-b2e3a4e4bc57",
+            "Max": 10,
+            "Min": 2,
+            "ModifyIndex": 10,
+            "Namespace": "default",
+            "Policy": {
+                "cooldown": "2m",
+                "evaluation_interval": "10s",
+                "check": [
+                    {
+                        "avg_sessions": {
+                            "source": "prometheus",
+                            "strategy": [{"target-value": {"target": 10}}]
+                        }
+                    }
+                ]
+            },
+            "Target": {
+                "Namespace": "default",
+                "Job": "webapp",
+                "Group": "demo"
+            },
+            "Type": "horizontal"
+        }
+        "#;
+
+        let policy: ScalingPolicy = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(policy.target.group.as_deref(), Some("demo"));
+        assert_eq!(policy.policy["cooldown"], "2m");
+        assert!(policy.policy["check"].is_array());
+    }
+
+    #[test]
+    fn scaling_target_other_keys() {
+        let js = r#"{"aws_asg_name": "hashistack-nomad-client", "node_class": "hashistack"}"#;
+        let target: ScalingTarget = serde_json::from_str(js).expect("deserialize failed");
+        assert!(target.group.is_none());
+        assert_eq!(target.other["aws_asg_name"], "hashistack-nomad-client");
+
+        let js = serde_json::to_value(&target).expect("serialize failed");
+        assert_eq!(js["node_class"], "hashistack");
+        assert!(js.get("Group").is_none());
+    }
+
+    #[test]
+    fn deserialize_job_scale_status() {
+        let js = r#"
+        {
+            "JobCreateIndex": 10,
+            "JobID": "example",
+            "Namespace": "default",
+            "JobModifyIndex": 18,
+            "JobStopped": false,
+            "TaskGroups": {
+                "cache": {
+                    "Desired": 1,
+                    "Events": [
+                        {
+                            "Count": 3,
+                            "CreateIndex": 16,
+                            "Error": false,
+                            "EvalID": "ebe0c6c0-01bc-8b47-4cba-1b7dc6e6bd5a",
+                            "Message": "submitted using the Nomad CLI",
+                            "Meta": null,
+                            "PreviousCount": 1,
+                            "Time": 1587667783048565000
+                        }
+                    ],
+                    "Healthy": 1,
+                    "Placed": 1,
+                    "Running": 0,
+                    "Unhealthy": 0
+                }
+            }
+        }
+        "#;
+        let status: JobScaleStatusResponse = serde_json::from_str(js).expect("deserialize failed");
+        let cache = &status.task_groups["cache"];
+        assert_eq!(cache.desired, 1);
+        assert_eq!(cache.events[0].count, Some(3));
+    }
+}
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_with::rust::default_on_null;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+// Context is the kind of object searched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Context {
+    Jobs,
+    Evals,
+    Allocs,
+    Nodes,
+    #[serde(rename = "deployment")]
+    Deployments,
+    Plugins,
+    Volumes,
+    Namespaces,
+    ScalingPolicy,
+    #[serde(rename = "vars")]
+    Variables,
+    All,
+    // Contexts added by newer versions of nomad
+    #[serde(other)]
+    Other,
+}
+
+impl Context {
+    // Contexts whose objects are identified by a UUID
+    pub fn is_uuid(&self) -> bool {
+        matches!(
+            self,
+            Context::Evals | Context::Allocs | Context::Nodes | Context::Deployments
+        )
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Context::Jobs => "jobs",
+            Context::Evals => "evals",
+            Context::Allocs => "allocs",
+            Context::Nodes => "nodes",
+            Context::Deployments => "deployment",
+            Context::Plugins => "plugins",
+            Context::Volumes => "volumes",
+            Context::Namespaces => "namespaces",
+            Context::ScalingPolicy => "scaling_policy",
+            Context::Variables => "vars",
+            Context::All => "all",
+            Context::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SearchRequest {
+    pub prefix: String,
+    pub context: Context,
+}
+
+// SearchResponse holds the IDs matching a prefix in each context. Contexts
+// with more matches than were returned are flagged in `truncations`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SearchResponse {
+    #[serde(deserialize_with = "null_lists")]
+    pub matches: HashMap<Context, Vec<String>>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub truncations: HashMap<Context, bool>,
+}
+
+impl SearchResponse {
+    pub fn matches(&self, context: Context) -> &[String] {
+        self.matches.get(&context).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn is_truncated(&self, context: Context) -> bool {
+        self.truncations.get(&context).copied().unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FuzzySearchRequest {
+    pub text: String,
+    pub context: Context,
+}
+
+// FuzzyMatch is an object whose name contains the search text. `scope` is
+// the path to the object, such as the namespace and job of a task group.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct FuzzyMatch {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub scope: Vec<String>,
+}
+
+// FuzzySearchResponse is keyed by context name as fuzzy search also reports
+// matching "groups", "tasks", "services", "images" and "commands"
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct FuzzySearchResponse {
+    #[serde(deserialize_with = "null_lists")]
+    pub matches: HashMap<String, Vec<FuzzyMatch>>,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub truncations: HashMap<String, bool>,
+}
+
+impl FuzzySearchResponse {
+    pub fn matches(&self, context: &str) -> &[FuzzyMatch] {
+        self.matches.get(context).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn is_truncated(&self, context: &str) -> bool {
+        self.truncations.get(context).copied().unwrap_or(false)
+    }
+}
+
+// Contexts without matches have a null list
+fn null_lists<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, Vec<V>>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+{
+    let lists: Option<HashMap<K, Option<Vec<V>>>> = Option::deserialize(deserializer)?;
+    Ok(lists
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, v)| (k, v.unwrap_or_default()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_search_response() {
+        let js = r#"
+        {
+            "Matches": {
+                "allocs": ["8f3a1b2c-0000-0000-0000-000000000000"],
+                "jobs": null,
+                "quotas": []
+            },
+            "Truncations": {"allocs": false, "jobs": true}
+        }
+        "#;
+        let response: SearchResponse = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(response.matches(Context::Allocs).len(), 1);
+        assert!(response.matches(Context::Jobs).is_empty());
+        assert!(response.is_truncated(Context::Jobs));
+    }
+
+    #[test]
+    fn deserialize_fuzzy_response() {
+        let js = r#"
+        {
+            "Matches": {
+                "jobs": [{"ID": "redis", "Scope": ["default"]}],
+                "groups": [{"ID": "cache", "Scope": ["default", "redis"]}]
+            },
+            "Truncations": {"jobs": false, "groups": false}
+        }
+        "#;
+        let response: FuzzySearchResponse = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(
+            response.matches("groups")[0].scope,
+            vec!["default", "redis"]
+        );
+        assert_eq!(
+            serde_json::to_value(Context::ScalingPolicy).unwrap(),
+            "scaling_policy"
+        );
+    }
+}
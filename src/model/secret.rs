@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+// Secret wraps a sensitive value such as a token or credential. It serializes
+// exactly like the wrapped value but prints as `<redacted>` in Debug output so
+// structs holding secrets can be logged safely.
+#[derive(Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    // Access the wrapped value, making reads of the secret easy to spot
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl<T> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Secret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(value: &str) -> Self {
+        Secret(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn debug_is_redacted() {
+        let secret: Secret<String> = "s3cr3t".into();
+        assert_eq!(format!("{:?}", secret), "<redacted>");
+        assert_eq!(
+            format!("{:#?}", Some(secret.clone())),
+            "Some(\n    <redacted>,\n)"
+        );
+        assert_eq!(secret.expose(), "s3cr3t");
+    }
+
+    #[test]
+    fn serializes_transparently() {
+        let mut env: HashMap<String, Secret<String>> = HashMap::new();
+        env.insert("TOKEN".to_string(), "abc".into());
+        let js = serde_json::to_string(&env).expect("serialize failed");
+        assert_eq!(js, r#"{"TOKEN":"abc"}"#);
+
+        let back: HashMap<String, Secret<String>> =
+            serde_json::from_str(&js).expect("deserialize failed");
+        assert_eq!(back["TOKEN"].as_str(), "abc");
+        assert!(!format!("{:?}", back).contains("abc"));
+    }
+}
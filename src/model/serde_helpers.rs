@@ -61,6 +61,211 @@ pub mod hashi_duration {
     }
 }
 
+pub mod nanos_duration {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+    use std::convert::TryFrom;
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawDuration {
+        Nanos(i64),
+        Text(String),
+    }
+
+    //
+    // Deserialize go's time.Duration, an integer count of nanoseconds, also
+    // accepting a duration string such as "15s" as written in job specs
+    //
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<RawDuration>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(RawDuration::Nanos(n)) => u64::try_from(n)
+                .map(|n| Some(Duration::from_nanos(n)))
+                .map_err(|_| D::Error::custom(format!("negative duration {}", n))),
+            Some(RawDuration::Text(s)) => super::readable_duration::parse(&s)
+                .map(Some)
+                .map_err(D::Error::custom),
+        }
+    }
+
+    //
+    // Serialize a duration as an integer count of nanoseconds
+    //
+    pub fn serialize<S>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(duration) => {
+                let nanos = i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX);
+                serializer.serialize_i64(nanos)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+pub mod readable_duration {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    //
+    // Parse a go duration string such as "200ms", "10s" or "1h30m"
+    //
+    pub fn parse(s: &str) -> Result<Duration, String> {
+        match s {
+            "" => return Err("empty duration".to_string()),
+            "0" => return Ok(Duration::from_secs(0)),
+            _ => {}
+        }
+        let mut total = 0f64;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .ok_or_else(|| format!("missing unit in duration \"{}\"", s))?;
+            let value: f64 = rest[..digits]
+                .parse()
+                .map_err(|_| format!("invalid duration \"{}\"", s))?;
+            rest = &rest[digits..];
+            let unit = rest
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .unwrap_or(rest.len());
+            let scale = match &rest[..unit] {
+                "ns" => 1e-9,
+                "us" | "\u{b5}s" | "\u{3bc}s" => 1e-6,
+                "ms" => 1e-3,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 3600.0,
+                other => return Err(format!("unknown unit \"{}\" in duration \"{}\"", other, s)),
+            };
+            total += value * scale;
+            rest = &rest[unit..];
+        }
+        Duration::try_from_secs_f64(total).map_err(|_| format!("duration \"{}\" out of range", s))
+    }
+
+    //
+    // Format a duration the way go does; "200ms", "10s", "1h0m30s"
+    //
+    pub fn format(d: &Duration) -> String {
+        let nanos = d.as_nanos();
+        if nanos == 0 {
+            return "0s".to_string();
+        }
+        if nanos < 1_000 {
+            return format!("{}ns", nanos);
+        }
+        if nanos < 1_000_000 {
+            return format!("{}\u{b5}s", trim(nanos as f64 / 1e3));
+        }
+        if nanos < 1_000_000_000 {
+            return format!("{}ms", trim(nanos as f64 / 1e6));
+        }
+        let secs = d.as_secs();
+        let (hours, minutes) = (secs / 3600, (secs % 3600) / 60);
+        let seconds = trim((secs % 60) as f64 + f64::from(d.subsec_nanos()) / 1e9);
+        match (hours, minutes) {
+            (0, 0) => format!("{}s", seconds),
+            (0, m) => format!("{}m{}s", m, seconds),
+            (h, m) => format!("{}h{}m{}s", h, m, seconds),
+        }
+    }
+
+    fn trim(value: f64) -> String {
+        let s = format!("{:.9}", value);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) => parse(&s).map_err(D::Error::custom),
+            None => Ok(Duration::default()),
+        }
+    }
+
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format(value))
+    }
+}
+
+pub mod base64_bytes {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    //
+    // Deserialize a base64 encoded string (go's encoding of []byte) treating
+    // null as empty
+    //
+    #[allow(dead_code)]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(encoded) => base64::decode(encoded).map_err(D::Error::custom),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn serialize<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if value.is_empty() {
+            serializer.serialize_none()
+        } else {
+            serializer.serialize_str(&base64::encode(value))
+        }
+    }
+}
+
+pub mod single_block {
+    use serde::{de::Error as _, Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawBlock<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    //
+    // Deserialize a block nested in a driver config, written as an object or
+    // as a list holding one object by HCL to JSON conversion. null and an
+    // empty list are treated as unset.
+    //
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + Default,
+    {
+        match Option::<RawBlock<T>>::deserialize(deserializer)? {
+            None => Ok(T::default()),
+            Some(RawBlock::One(value)) => Ok(value),
+            Some(RawBlock::Many(mut values)) => match values.len() {
+                0 => Ok(T::default()),
+                1 => Ok(values.remove(0)),
+                n => Err(D::Error::custom(format!(
+                    "expected a single block, found {}",
+                    n
+                ))),
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +280,20 @@ mod tests {
         pub duration: Option<Duration>,
     }
 
+    #[derive(Debug, Default, Deserialize, Serialize)]
+    #[serde(default)]
+    struct HasNanos {
+        #[serde(with = "nanos_duration")]
+        pub duration: Option<Duration>,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize)]
+    #[serde(default)]
+    struct HasBytes {
+        #[serde(with = "base64_bytes")]
+        pub payload: Vec<u8>,
+    }
+
     #[test]
     fn deserialize_hashi_duration_integer() {
         let d1: HasDuration = serde_json::from_str(r#"{"duration":10}"#).expect("de failed");
@@ -88,6 +307,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn deserialize_hashi_duration_string_units() {
         let d3: HasDuration = serde_json::from_str(r#"{"duration":"1h"}"#).expect("de failed");
         assert_eq!(d3.duration.unwrap(), Duration::from_secs(1 * 60 * 60));
@@ -107,4 +327,58 @@ mod tests {
         let d: HasDuration = serde_json::from_str(r#"{"duration":null}"#).expect("de failed");
         assert!(d.duration.is_none());
     }
+
+    #[test]
+    fn nanos_duration_round_trip() {
+        let d: HasNanos = serde_json::from_str(r#"{"duration":5000000000}"#).expect("de failed");
+        assert_eq!(d.duration.unwrap(), Duration::from_secs(5));
+        let js = serde_json::to_string(&d).expect("ser failed");
+        assert_eq!(js, r#"{"duration":5000000000}"#);
+
+        let d: HasNanos = serde_json::from_str(r#"{"duration":"1m30s"}"#).expect("de failed");
+        assert_eq!(d.duration.unwrap(), Duration::from_secs(90));
+        let d: HasNanos = serde_json::from_str(r#"{"duration":null}"#).expect("de failed");
+        assert!(d.duration.is_none());
+        assert!(serde_json::from_str::<HasNanos>(r#"{"duration":-1}"#).is_err());
+    }
+
+    #[test]
+    fn base64_bytes_round_trip() {
+        let b = HasBytes {
+            payload: b"hello".to_vec(),
+        };
+        let js = serde_json::to_string(&b).expect("ser failed");
+        assert_eq!(js, r#"{"payload":"aGVsbG8="}"#);
+        let b: HasBytes = serde_json::from_str(&js).expect("de failed");
+        assert_eq!(b.payload, b"hello");
+    }
+
+    #[test]
+    fn base64_bytes_null() {
+        let b: HasBytes = serde_json::from_str(r#"{"payload":null}"#).expect("de failed");
+        assert!(b.payload.is_empty());
+        let js = serde_json::to_string(&b).expect("ser failed");
+        assert_eq!(js, r#"{"payload":null}"#);
+    }
+
+    #[test]
+    fn readable_duration_round_trip() {
+        for (text, duration) in &[
+            ("200ms", Duration::from_millis(200)),
+            ("10s", Duration::from_secs(10)),
+            ("1m30s", Duration::from_secs(90)),
+            ("1h0m0s", Duration::from_secs(3600)),
+            ("0s", Duration::from_secs(0)),
+        ] {
+            assert_eq!(readable_duration::parse(text).unwrap(), *duration);
+            assert_eq!(readable_duration::format(duration), *text);
+        }
+        assert_eq!(
+            readable_duration::parse("1.5s").unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert!(readable_duration::parse("10").is_err());
+        assert!(readable_duration::parse("10d").is_err());
+        assert!(readable_duration::parse("99999999999999999999999h").is_err());
+    }
 }
@@ -2,18 +2,19 @@ use serde::{Deserialize, Serialize};
 use serde_with::rust::{default_on_error, default_on_null};
 
 use std::collections::HashMap;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
 use super::resources::Resources;
-use super::serde_helpers::hashi_duration;
+use super::secret::Secret;
+use super::serde_helpers::nanos_duration;
 use super::tasks::LogConfig;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct CheckRestart {
     pub limit: Option<i64>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub grace: Option<Duration>,
     pub ignore_warnings: bool,
 }
@@ -33,9 +34,9 @@ pub struct ServiceCheck {
     pub port_label: Option<String>,
     pub expose: bool,
     pub address_mode: Option<String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub interval: Option<Duration>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub timeout: Option<Duration>,
     #[serde(rename = "TLSSkipVerify")]
     pub tls_skip_verify: bool,
@@ -52,6 +53,29 @@ pub struct ServiceCheck {
     pub failures_before_critical: Option<i64>,
 }
 
+impl ServiceCheck {
+    // An HTTP check passing on a 2xx response from `path`
+    pub fn http(path: &str, interval: Duration, timeout: Duration) -> Self {
+        ServiceCheck {
+            check_type: Some("http".to_string()),
+            path: Some(path.to_string()),
+            interval: Some(interval),
+            timeout: Some(timeout),
+            ..Default::default()
+        }
+    }
+
+    // A TCP check passing once the port accepts connections
+    pub fn tcp(interval: Duration, timeout: Duration) -> Self {
+        ServiceCheck {
+            check_type: Some("tcp".to_string()),
+            interval: Some(interval),
+            timeout: Some(timeout),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct Service {
@@ -75,6 +99,31 @@ pub struct Service {
     pub task_name: Option<String>,
 }
 
+impl Service {
+    pub fn new(name: &str) -> Self {
+        Service {
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    // Advertise the service on the network port with this label
+    pub fn with_port(mut self, label: &str) -> Self {
+        self.port_label = Some(label.to_string());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn with_check(mut self, check: ServiceCheck) -> Self {
+        self.checks.push(check);
+        self
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct ConsulConnect {
@@ -104,7 +153,7 @@ pub struct ConsulGatewayBindAddress {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct ConsulGatewayProxy {
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub connect_timeout: Option<Duration>,
     pub envoy_gateway_bind_tagged_address: bool,
     #[serde(deserialize_with = "default_on_null::deserialize")]
@@ -140,14 +189,14 @@ pub struct SidecarTask {
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub config: HashMap<String, serde_json::Value>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub env: HashMap<String, String>,
+    pub env: HashMap<String, Secret<String>>,
     pub resources: Option<Resources>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub meta: HashMap<String, String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub kill_timeout: Option<Duration>,
     pub log_config: Option<LogConfig>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub shutdown_delay: Option<Duration>,
     pub kill_signal: Option<String>,
 }
@@ -240,3 +289,87 @@ pub struct ConsulTerminatingConfigEntry {
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub services: Vec<ConsulLinkedService>,
 }
+
+// ServiceRegistration is an instance of a service registered with Nomad's
+// built-in service provider
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRegistration {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub service_name: String,
+    pub namespace: String,
+    #[serde(rename = "NodeID")]
+    pub node_id: String,
+    pub datacenter: String,
+    #[serde(rename = "JobID")]
+    pub job_id: String,
+    #[serde(rename = "AllocID")]
+    pub alloc_id: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub tags: Vec<String>,
+    pub address: String,
+    pub port: u16,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+impl ServiceRegistration {
+    //
+    // The address the instance is reachable on, `None` if the registered
+    // address is a hostname rather than an IP
+    //
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        let ip: IpAddr = self.address.parse().ok()?;
+        Some(SocketAddr::new(ip, self.port))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+pub const CHECK_STATUS_SUCCESS: &str = "success";
+pub const CHECK_STATUS_FAILURE: &str = "failure";
+pub const CHECK_STATUS_PENDING: &str = "pending";
+
+// Checks which determine the health of a service, readiness checks
+// (`on_update = "ignore"`) do not
+pub const CHECK_MODE_HEALTHINESS: &str = "healthiness";
+pub const CHECK_MODE_READINESS: &str = "readiness";
+
+// AllocCheckStatus is the latest result of a check of the built-in service
+// provider, run by the client of the allocation
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AllocCheckStatus {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub check: String,
+    pub group: String,
+    pub mode: String,
+    pub output: String,
+    pub service: String,
+    pub task: String,
+    pub status: String,
+    pub status_code: i64,
+    // Unix time in seconds
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRegistrationStub {
+    pub service_name: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub tags: Vec<String>,
+}
+
+// ServiceRegistrationListStub groups the registered service names by namespace
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRegistrationListStub {
+    pub namespace: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub services: Vec<ServiceRegistrationStub>,
+}
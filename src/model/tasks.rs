@@ -5,12 +5,14 @@ use serde_with::rust::default_on_null;
 use std::collections::HashMap;
 use std::time::Duration;
 
+use super::builder::{TaskBuilder, TaskGroupBuilder};
 use super::constraint::Constraint;
 use super::csi::CSIMountOptions;
 use super::jobs::UpdateStrategy;
 use super::resources::{NetworkResource, Resources};
 use super::scaling::ScalingPolicy;
-use super::serde_helpers::hashi_duration;
+use super::secret::Secret;
+use super::serde_helpers::nanos_duration;
 use super::services::Service;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -22,13 +24,25 @@ pub struct Affinity {
     pub weight: Option<i8>,
 }
 
+impl Affinity {
+    // A placement preference, `weight` ranges from -100 (avoid) to 100 (prefer)
+    pub fn new(attribute: &str, operand: &str, value: &str, weight: i8) -> Self {
+        Affinity {
+            l_target: Some(attribute.to_string()),
+            r_target: Some(value.to_string()),
+            operand: Some(operand.to_string()),
+            weight: Some(weight),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct RestartPolicy {
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub interval: Option<Duration>,
     pub attempts: Option<i64>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub delay: Option<Duration>,
     pub mode: Option<String>,
 }
@@ -47,6 +61,31 @@ pub struct VolumeRequest {
     pub extra_key_hcl: Vec<String>,
 }
 
+impl VolumeRequest {
+    // A host volume declared in the client configuration as `source`
+    pub fn host(source: &str) -> Self {
+        VolumeRequest {
+            volume_type: Some("host".to_string()),
+            source: Some(source.to_string()),
+            ..Default::default()
+        }
+    }
+
+    // A CSI volume registered with the ID `source`
+    pub fn csi(source: &str) -> Self {
+        VolumeRequest {
+            volume_type: Some("csi".to_string()),
+            source: Some(source.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = Some(true);
+        self
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct TaskState {
@@ -94,20 +133,20 @@ pub struct Task {
     pub user: Option<String>,
     pub lifecycle: Option<TaskLifecycle>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub config: HashMap<String, serde_json::Value>, // FIXME: Figure out value type
+    pub config: HashMap<String, serde_json::Value>, // See `Task::driver_config`
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub constraints: Vec<Constraint>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub affinities: Vec<Affinity>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub env: HashMap<String, String>,
+    pub env: HashMap<String, Secret<String>>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub services: Vec<Service>,
     pub resources: Option<Resources>,
     pub restart_policy: Option<RestartPolicy>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub meta: HashMap<String, String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub kill_timeout: Option<Duration>,
     pub log_config: Option<LogConfig>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
@@ -120,7 +159,7 @@ pub struct Task {
     pub volume_mounts: Vec<VolumeMount>,
     pub csi_plugin_config: Option<TaskCSIPluginConfig>,
     pub leader: bool,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub shutdown_delay: Option<Duration>,
     pub kill_signal: Option<String>,
     pub kind: Option<String>,
@@ -128,14 +167,20 @@ pub struct Task {
     pub scaling_policy: Vec<ScalingPolicy>,
 }
 
+impl Task {
+    pub fn builder(name: &str, driver: &str) -> TaskBuilder {
+        TaskBuilder::new(name, driver)
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct TaskArtifact {
     pub getter_source: Option<String>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub getter_options: HashMap<String, String>,
+    pub getter_options: HashMap<String, Secret<String>>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
-    pub getter_headers: HashMap<String, String>,
+    pub getter_headers: HashMap<String, Secret<String>>,
     pub getter_mode: Option<String>,
     pub relative_dest: Option<String>,
 }
@@ -175,21 +220,27 @@ pub struct TaskGroup {
     pub meta: HashMap<String, String>,
     #[serde(deserialize_with = "default_on_null::deserialize")]
     pub services: Vec<Service>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub shutdown_delay: Option<Duration>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub stop_after_client_disconnect: Option<Duration>,
     pub scaling: Option<ScalingPolicy>,
 }
 
+impl TaskGroup {
+    pub fn builder(name: &str) -> TaskGroupBuilder {
+        TaskGroupBuilder::new(name)
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct MigrateStrategy {
     pub max_parallel: Option<i64>,
     pub health_check: Option<String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub min_healthy_time: Option<Duration>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub healthy_deadline: Option<Duration>,
 }
 
@@ -197,12 +248,12 @@ pub struct MigrateStrategy {
 #[serde(default, rename_all = "PascalCase")]
 pub struct ReschedulePolicy {
     pub attempts: Option<i64>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub interval: Option<Duration>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub delay: Option<Duration>,
     pub delay_function: Option<String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub max_delay: Option<Duration>,
     pub unlimited: Option<bool>,
 }
@@ -216,6 +267,26 @@ pub struct Spread {
     pub spread_target: Vec<SpreadTarget>,
 }
 
+impl Spread {
+    // Spread allocations evenly over the values of `attribute`
+    pub fn new(attribute: &str, weight: i8) -> Self {
+        Spread {
+            attribute: Some(attribute.to_string()),
+            weight: Some(weight),
+            ..Default::default()
+        }
+    }
+
+    // Place `percent` of the allocations on nodes where the attribute is `value`
+    pub fn with_target(mut self, value: &str, percent: u8) -> Self {
+        self.spread_target.push(SpreadTarget {
+            value: value.to_string(),
+            percent: Some(percent),
+        });
+        self
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct SpreadTarget {
@@ -255,19 +326,57 @@ impl Default for LogConfig {
 pub struct Template {
     pub source_path: Option<String>,
     pub dest_path: Option<String>,
-    pub embedded_tmpl: Option<String>,
+    pub embedded_tmpl: Option<Secret<String>>,
     pub change_mode: Option<String>,
     pub change_signal: Option<String>,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub splay: Option<Duration>,
     pub perms: Option<String>,
     pub left_delim: Option<String>,
     pub right_delim: Option<String>,
     pub envvars: bool,
-    #[serde(with = "hashi_duration")]
+    #[serde(with = "nanos_duration")]
     pub vault_grace: Option<Duration>,
 }
 
+impl Template {
+    // Render `data` to `destination`, relative to the task directory
+    pub fn inline(data: &str, destination: &str) -> Self {
+        Template {
+            embedded_tmpl: Some(data.into()),
+            dest_path: Some(destination.to_string()),
+            ..Default::default()
+        }
+    }
+
+    // Render the template file at `source` to `destination`
+    pub fn file(source: &str, destination: &str) -> Self {
+        Template {
+            source_path: Some(source.to_string()),
+            dest_path: Some(destination.to_string()),
+            ..Default::default()
+        }
+    }
+
+    // Signal the task with `signal` when the rendered output changes
+    pub fn with_signal(mut self, signal: &str) -> Self {
+        self.change_mode = Some("signal".to_string());
+        self.change_signal = Some(signal.to_string());
+        self
+    }
+
+    pub fn with_change_mode(mut self, mode: &str) -> Self {
+        self.change_mode = Some(mode.to_string());
+        self
+    }
+
+    // Load the rendered output into the task's environment
+    pub fn with_env(mut self) -> Self {
+        self.envvars = true;
+        self
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct Vault {
@@ -291,6 +400,7 @@ pub struct TaskCSIPluginConfig {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use super::*;
 
@@ -0,0 +1,339 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::jobs::Job;
+use super::services::Service;
+use super::tasks::{Affinity, Spread, Task, TaskGroup};
+use crate::error::{Error, Result};
+
+// ValidationError is a problem with a job spec, located by the JSON path of
+// the offending field such as `TaskGroups[0].Tasks[1].Resources.MemoryMB`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl Job {
+    //
+    // Check the job for the mistakes the server would reject it for,
+    // returning `Error::Validation` with every problem found
+    //
+    pub fn validate(&self) -> Result<()> {
+        let mut v = Validator::default();
+        v.job(self);
+        v.finish()
+    }
+}
+
+impl TaskGroup {
+    // Validate the group on its own, paths are relative to the group
+    pub fn validate(&self) -> Result<()> {
+        let mut v = Validator::default();
+        v.group("", self);
+        v.finish()
+    }
+}
+
+impl Task {
+    //
+    // Validate the task on its own, paths are relative to the task. Ports and
+    // volumes are declared by the group so are only checked by
+    // `TaskGroup::validate` and `Job::validate`.
+    //
+    pub fn validate(&self) -> Result<()> {
+        let mut v = Validator::default();
+        v.task("", self);
+        v.finish()
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, path: String, message: String) {
+        self.errors.push(ValidationError { path, message });
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(self.errors))
+        }
+    }
+
+    fn job(&mut self, job: &Job) {
+        match job.id.as_deref().unwrap_or_default() {
+            "" => self.error("ID".to_string(), "missing job ID".to_string()),
+            id if id.contains(' ') => {
+                self.error("ID".to_string(), "job ID contains a space".to_string())
+            }
+            _ => {}
+        }
+        if let Some(priority) = job.priority {
+            if !(1..=100).contains(&priority) {
+                self.error(
+                    "Priority".to_string(),
+                    format!("priority {} is outside 1..100", priority),
+                );
+            }
+        }
+        self.affinities("", &job.affinities);
+        self.spreads("", &job.spreads);
+
+        if job.task_groups.is_empty() {
+            self.error("TaskGroups".to_string(), "missing task groups".to_string());
+        }
+        let mut names = HashSet::new();
+        for (i, group) in job.task_groups.iter().enumerate() {
+            let path = format!("TaskGroups[{}]", i);
+            if !group.name.is_empty() && !names.insert(group.name.as_str()) {
+                self.error(
+                    field(&path, "Name"),
+                    format!("duplicate group \"{}\"", group.name),
+                );
+            }
+            self.group(&path, group);
+        }
+    }
+
+    fn group(&mut self, path: &str, group: &TaskGroup) {
+        if group.name.is_empty() {
+            self.error(field(path, "Name"), "missing group name".to_string());
+        }
+        if let Some(count) = group.count {
+            if count < 0 {
+                self.error(field(path, "Count"), format!("negative count {}", count));
+            }
+        }
+        self.affinities(path, &group.affinities);
+        self.spreads(path, &group.spreads);
+
+        let mut ports = HashSet::new();
+        for network in &group.networks {
+            let labels = network.reserved_ports.iter().chain(&network.dynamic_ports);
+            ports.extend(labels.filter_map(|p| p.label.as_deref()));
+        }
+        for task in &group.tasks {
+            for network in task.resources.iter().flat_map(|r| &r.networks) {
+                let labels = network.reserved_ports.iter().chain(&network.dynamic_ports);
+                ports.extend(labels.filter_map(|p| p.label.as_deref()));
+            }
+        }
+        self.services(path, &group.services, &ports);
+
+        if group.tasks.is_empty() {
+            self.error(field(path, "Tasks"), "missing tasks".to_string());
+        }
+        let mut names = HashSet::new();
+        for (i, task) in group.tasks.iter().enumerate() {
+            let task_path = field(path, &format!("Tasks[{}]", i));
+            if !task.name.is_empty() && !names.insert(task.name.as_str()) {
+                self.error(
+                    field(&task_path, "Name"),
+                    format!("duplicate task \"{}\"", task.name),
+                );
+            }
+            self.task(&task_path, task);
+            self.services(&task_path, &task.services, &ports);
+            for (j, mount) in task.volume_mounts.iter().enumerate() {
+                let volume = mount.volume.as_deref().unwrap_or_default();
+                if !group.volumes.contains_key(volume) {
+                    self.error(
+                        field(&task_path, &format!("VolumeMounts[{}].Volume", j)),
+                        format!("volume \"{}\" is not declared by the group", volume),
+                    );
+                }
+            }
+        }
+    }
+
+    fn task(&mut self, path: &str, task: &Task) {
+        if task.name.is_empty() {
+            self.error(field(path, "Name"), "missing task name".to_string());
+        }
+        if task.driver.as_deref().unwrap_or_default().is_empty() {
+            self.error(field(path, "Driver"), "missing task driver".to_string());
+        }
+        if let Some(resources) = &task.resources {
+            if let Some(cpu) = resources.cpu.filter(|&cpu| cpu < 1) {
+                self.error(
+                    field(path, "Resources.CPU"),
+                    format!("minimum CPU value is 1, got {}", cpu),
+                );
+            }
+            if let Some(memory) = resources.memory_mb.filter(|&memory| memory < 10) {
+                self.error(
+                    field(path, "Resources.MemoryMB"),
+                    format!("minimum MemoryMB value is 10, got {}", memory),
+                );
+            }
+        }
+        self.affinities(path, &task.affinities);
+    }
+
+    fn affinities(&mut self, path: &str, affinities: &[Affinity]) {
+        for (i, affinity) in affinities.iter().enumerate() {
+            let path = field(path, &format!("Affinities[{}].Weight", i));
+            match affinity.weight {
+                Some(0) => self.error(path, "weight cannot be zero".to_string()),
+                Some(weight) if !(-100..=100).contains(&weight) => {
+                    self.error(path, format!("weight {} is outside -100..100", weight))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn spreads(&mut self, path: &str, spreads: &[Spread]) {
+        for (i, spread) in spreads.iter().enumerate() {
+            let total: u32 = spread
+                .spread_target
+                .iter()
+                .map(|t| u32::from(t.percent.unwrap_or_default()))
+                .sum();
+            if total > 100 {
+                self.error(
+                    field(path, &format!("Spreads[{}].SpreadTarget", i)),
+                    format!("target percentages sum to {}, over 100", total),
+                );
+            }
+        }
+    }
+
+    // Services must name a port declared by a network of the group. A port
+    // number rather than a label is allowed for driver address mode.
+    fn services(&mut self, path: &str, services: &[Service], ports: &HashSet<&str>) {
+        let known =
+            |label: &str| label.is_empty() || ports.contains(label) || label.parse::<u16>().is_ok();
+        for (i, service) in services.iter().enumerate() {
+            let service_path = field(path, &format!("Services[{}]", i));
+            let label = service.port_label.as_deref().unwrap_or_default();
+            if !known(label) {
+                self.error(
+                    field(&service_path, "PortLabel"),
+                    format!("port \"{}\" is not defined", label),
+                );
+            }
+            for (j, check) in service.checks.iter().enumerate() {
+                let label = check.port_label.as_deref().unwrap_or_default();
+                if !known(label) {
+                    self.error(
+                        field(&service_path, &format!("Checks[{}].PortLabel", j)),
+                        format!("port \"{}\" is not defined", label),
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Join the path of a struct and one of its fields
+fn field(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::resources::{NetworkResource, Resources};
+    use crate::model::tasks::{SpreadTarget, VolumeMount};
+
+    fn paths(result: Result<()>) -> Vec<String> {
+        match result {
+            Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.path).collect(),
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_job() {
+        let task = |name: &str| Task {
+            name: name.to_string(),
+            driver: Some("docker".to_string()),
+            ..Default::default()
+        };
+        let mut job = Job::service("web")
+            .group(
+                TaskGroup::builder("web")
+                    .network(NetworkResource::bridge().with_port("http"))
+                    .service(Service::new("web").with_port("http"))
+                    .task(Task::builder("server", "docker")),
+            )
+            .build()
+            .expect("build");
+        assert!(job.validate().is_ok());
+
+        job.name = None;
+        job.affinities
+            .push(Affinity::new("${node.class}", "=", "large", 120));
+        job.affinities
+            .push(Affinity::new("${node.class}", "=", "small", 0));
+        job.spreads.push(Spread {
+            spread_target: vec![
+                SpreadTarget {
+                    value: "dc1".to_string(),
+                    percent: Some(70),
+                },
+                SpreadTarget {
+                    value: "dc2".to_string(),
+                    percent: Some(40),
+                },
+            ],
+            ..Default::default()
+        });
+        let group = &mut job.task_groups[0];
+        group.count = Some(-1);
+        group
+            .services
+            .push(Service::new("admin").with_port("admin"));
+        group.tasks.push(Task {
+            driver: None,
+            resources: Some(Resources::new(100, 4)),
+            ..task("server")
+        });
+        group.tasks.push(Task {
+            volume_mounts: vec![VolumeMount {
+                volume: Some("data".to_string()),
+                ..Default::default()
+            }],
+            ..task("sidecar")
+        });
+        let mut other = group.clone();
+        other.tasks.clear();
+        job.task_groups.push(other);
+
+        assert_eq!(
+            paths(job.validate()),
+            vec![
+                "Affinities[0].Weight",
+                "Affinities[1].Weight",
+                "Spreads[0].SpreadTarget",
+                "TaskGroups[0].Count",
+                "TaskGroups[0].Services[1].PortLabel",
+                "TaskGroups[0].Tasks[1].Name",
+                "TaskGroups[0].Tasks[1].Driver",
+                "TaskGroups[0].Tasks[1].Resources.MemoryMB",
+                "TaskGroups[0].Tasks[2].VolumeMounts[0].Volume",
+                "TaskGroups[1].Name",
+                "TaskGroups[1].Count",
+                "TaskGroups[1].Services[1].PortLabel",
+                "TaskGroups[1].Tasks",
+            ]
+        );
+    }
+}
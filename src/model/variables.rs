@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::secret::Secret;
+use super::serde_helpers::readable_duration;
+
+// VariableLock is held on a variable by a single holder, identified by the
+// lock ID, until it is released or its TTL expires without being renewed
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct VariableLock {
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(rename = "TTL", with = "readable_duration")]
+    pub ttl: Duration,
+    #[serde(with = "readable_duration")]
+    pub lock_delay: Duration,
+}
+
+// VariableMetadata is a variable without its items, as returned by listings
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct VariableMetadata {
+    pub namespace: String,
+    pub path: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub lock: Option<VariableLock>,
+    pub create_index: u64,
+    pub modify_index: u64,
+    pub create_time: i64,
+    pub modify_time: i64,
+}
+
+// Variable is an encrypted set of key/value items stored at a path
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Variable {
+    pub namespace: String,
+    pub path: String,
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub items: HashMap<String, Secret<String>>,
+    #[serde(
+        deserialize_with = "default_on_null::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lock: Option<VariableLock>,
+
+    // Server managed fields
+    pub create_index: u64,
+    pub modify_index: u64,
+    pub create_time: i64,
+    pub modify_time: i64,
+}
+
+impl Variable {
+    pub fn new(path: &str) -> Self {
+        Variable {
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_item(mut self, key: &str, value: &str) -> Self {
+        self.items.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn item(&self, key: &str) -> Option<&str> {
+        self.items.get(key).map(|v| v.expose().as_str())
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.as_ref().is_some_and(|l| !l.id.is_empty())
+    }
+
+    pub fn metadata(&self) -> VariableMetadata {
+        VariableMetadata {
+            namespace: self.namespace.clone(),
+            path: self.path.clone(),
+            lock: self.lock.clone(),
+            create_index: self.create_index,
+            modify_index: self.modify_index,
+            create_time: self.create_time,
+            modify_time: self.modify_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_variable() {
+        let js = r#"
+        {
+            "Namespace": "default",
+            "Path": "nomad/jobs/example",
+            "CreateIndex": 1457,
+            "ModifyIndex": 1457,
+            "CreateTime": 1662061717905426000,
+            "ModifyTime": 1662061717905426000,
+            "Items": {"db_password": "hunter2", "user": "app"},
+            "Lock": {"ID": "0f4d5d1e", "TTL": "15s", "LockDelay": "15s"}
+        }
+        "#;
+        let variable: Variable = serde_json::from_str(js).expect("deserialize failed");
+        assert_eq!(variable.item("user"), Some("app"));
+        assert!(variable.is_locked());
+        assert_eq!(
+            variable.lock.as_ref().map(|l| l.ttl),
+            Some(Duration::from_secs(15))
+        );
+        assert!(!format!("{:?}", variable).contains("hunter2"));
+        assert_eq!(variable.metadata().modify_index, 1457);
+    }
+}
@@ -0,0 +1,470 @@
+//
+// Resolve services registered with Nomad's built-in service provider to
+// socket addresses, keeping each set of instances current in the background
+// so services can call each other without Consul
+//
+use futures_util::stream::{self, Stream};
+use rand::Rng;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tower::discover::Change;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::client::{NomadClient, QueryOptions};
+use crate::error::Result;
+use crate::model::allocations::ALLOC_CLIENT_STATUS_RUNNING;
+use crate::model::services::ServiceRegistration;
+
+// Strategy selects which instance `Instances::pick` returns
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    #[default]
+    RoundRobin,
+    Random,
+    // The instance picked longest ago, instances never picked come first
+    LeastRecentlyUsed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolverOptions {
+    pub strategy: Strategy,
+    // Maximum time the agent holds each blocking query open. Allocation
+    // statuses and check results are rechecked at least this often, bounding
+    // how long a stopped or failing instance can still be picked.
+    pub wait_time: Duration,
+    // Delay between requests when a response does not advance the index and
+    // before retrying a failed request
+    pub poll_interval: Duration,
+}
+
+impl Default for ResolverOptions {
+    fn default() -> Self {
+        ResolverOptions {
+            strategy: Strategy::default(),
+            wait_time: Duration::from_secs(30),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+// ServiceDiscover reports instances joining and leaving as
+// `tower::discover::Change`s keyed by registration ID, for use with
+// `tower::balance`
+pub type ServiceDiscover = Pin<Box<dyn Stream<Item = Result<Change<String, SocketAddr>>> + Send>>;
+
+// Instances is the live set of running, healthy instances of a service having
+// all of a set of tags
+pub struct Instances {
+    name: String,
+    tags: Vec<String>,
+    strategy: Strategy,
+    current: watch::Sender<Arc<Vec<ServiceRegistration>>>,
+    selection: Mutex<Selection>,
+}
+
+#[derive(Default)]
+struct Selection {
+    next: usize,
+    last_used: HashMap<String, Instant>,
+}
+
+impl Instances {
+    fn new(name: &str, tags: Vec<String>, strategy: Strategy) -> Self {
+        let (current, _) = watch::channel(Arc::new(Vec::new()));
+        Instances {
+            name: name.to_string(),
+            tags,
+            strategy,
+            current,
+            selection: Mutex::new(Selection::default()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    // The current instances ordered by registration ID
+    pub fn registrations(&self) -> Arc<Vec<ServiceRegistration>> {
+        self.current.borrow().clone()
+    }
+
+    pub fn addrs(&self) -> Vec<SocketAddr> {
+        self.registrations()
+            .iter()
+            .filter_map(ServiceRegistration::socket_addr)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.borrow().is_empty()
+    }
+
+    //
+    // Select an instance using the configured strategy, `None` if no
+    // instances are healthy. Instances registered with a hostname rather
+    // than an IP are skipped.
+    //
+    pub fn pick(&self) -> Option<SocketAddr> {
+        let registrations = self.registrations();
+        let candidates: Vec<(&str, SocketAddr)> = registrations
+            .iter()
+            .filter_map(|r| r.socket_addr().map(|addr| (r.id.as_str(), addr)))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let mut selection = self.selection.lock().unwrap();
+        let chosen = match self.strategy {
+            Strategy::RoundRobin => {
+                let chosen = selection.next % candidates.len();
+                selection.next = selection.next.wrapping_add(1);
+                chosen
+            }
+            Strategy::Random => rand::thread_rng().gen_range(0..candidates.len()),
+            Strategy::LeastRecentlyUsed => {
+                let last_used = &selection.last_used;
+                (0..candidates.len())
+                    .min_by_key(|i| last_used.get(candidates[*i].0))
+                    .unwrap_or(0)
+            }
+        };
+        let (id, addr) = candidates[chosen];
+        if self.strategy == Strategy::LeastRecentlyUsed {
+            selection.last_used.insert(id.to_string(), Instant::now());
+        }
+        Some(addr)
+    }
+
+    // Follow changes to the instances, see `ServiceDiscover`
+    pub fn discover(&self) -> ServiceDiscover {
+        let state = DiscoverState {
+            receiver: self.current.subscribe(),
+            known: HashMap::new(),
+            pending: VecDeque::new(),
+            started: false,
+        };
+        Box::pin(stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(change) = state.pending.pop_front() {
+                    return Some((Ok(change), state));
+                }
+                if state.started {
+                    // The resolver has been dropped
+                    state.receiver.changed().await.ok()?;
+                }
+                state.started = true;
+                let registrations = state.receiver.borrow().clone();
+                state.diff(&registrations);
+            }
+        }))
+    }
+
+    //
+    // Replace the instances with those of `registrations` having the
+    // required tags, notifying watchers only if the set changed
+    //
+    fn replace(&self, mut registrations: Vec<ServiceRegistration>) {
+        registrations.retain(|r| self.tags.iter().all(|tag| r.has_tag(tag)));
+        registrations.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let ids: HashSet<&str> = registrations.iter().map(|r| r.id.as_str()).collect();
+        self.selection
+            .lock()
+            .unwrap()
+            .last_used
+            .retain(|id, _| ids.contains(id.as_str()));
+
+        self.current.send_if_modified(|current| {
+            if **current == registrations {
+                return false;
+            }
+            *current = Arc::new(registrations);
+            true
+        });
+    }
+}
+
+struct DiscoverState {
+    receiver: watch::Receiver<Arc<Vec<ServiceRegistration>>>,
+    known: HashMap<String, SocketAddr>,
+    pending: VecDeque<Change<String, SocketAddr>>,
+    started: bool,
+}
+
+impl DiscoverState {
+    fn diff(&mut self, registrations: &[ServiceRegistration]) {
+        let latest: HashMap<String, SocketAddr> = registrations
+            .iter()
+            .filter_map(|r| r.socket_addr().map(|addr| (r.id.clone(), addr)))
+            .collect();
+        for (id, addr) in self.known.iter() {
+            if latest.get(id) != Some(addr) {
+                self.pending.push_back(Change::Remove(id.clone()));
+            }
+        }
+        for (id, addr) in latest.iter() {
+            if self.known.get(id) != Some(addr) {
+                self.pending.push_back(Change::Insert(id.clone(), *addr));
+            }
+        }
+        self.known = latest;
+    }
+}
+
+type ServiceKey = (String, Vec<String>);
+type Followed = (Arc<Instances>, JoinHandle<()>);
+
+// ServiceResolver keeps the instances of each service it is asked about
+// current until it is dropped
+pub struct ServiceResolver {
+    client: NomadClient,
+    options: ResolverOptions,
+    services: Mutex<HashMap<ServiceKey, Followed>>,
+}
+
+impl ServiceResolver {
+    pub fn new(client: NomadClient, options: ResolverOptions) -> Self {
+        ServiceResolver {
+            client,
+            options,
+            services: Mutex::new(HashMap::new()),
+        }
+    }
+
+    //
+    // The healthy instances of a service having all of the given tags. The
+    // first call for a name and set of tags loads the instances and then
+    // follows them in the background.
+    //
+    pub async fn instances(&self, name: &str, tags: &[&str]) -> Result<Arc<Instances>> {
+        let mut tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        tags.sort();
+        tags.dedup();
+        let key = (name.to_string(), tags);
+        if let Some((instances, _)) = self.services.lock().unwrap().get(&key) {
+            return Ok(instances.clone());
+        }
+
+        let instances = Arc::new(Instances::new(name, key.1.clone(), self.options.strategy));
+        let index = refresh(&self.client, &instances, 0, &self.options).await?;
+
+        let mut services = self.services.lock().unwrap();
+        if let Some((existing, _)) = services.get(&key) {
+            // Lost a race with another caller for the same service
+            return Ok(existing.clone());
+        }
+        let handle = tokio::spawn(follow(
+            self.client.clone(),
+            instances.clone(),
+            index,
+            self.options.clone(),
+        ));
+        services.insert(key, (instances.clone(), handle));
+        Ok(instances)
+    }
+
+    // Pick an instance of the service, see `Instances::pick`
+    pub async fn resolve(&self, name: &str, tags: &[&str]) -> Result<Option<SocketAddr>> {
+        Ok(self.instances(name, tags).await?.pick())
+    }
+
+    pub async fn discover(&self, name: &str, tags: &[&str]) -> Result<ServiceDiscover> {
+        Ok(self.instances(name, tags).await?.discover())
+    }
+}
+
+impl Drop for ServiceResolver {
+    fn drop(&mut self) {
+        for (_, (_, handle)) in self.services.lock().unwrap().drain() {
+            handle.abort();
+        }
+    }
+}
+
+async fn follow(
+    client: NomadClient,
+    instances: Arc<Instances>,
+    mut index: u64,
+    options: ResolverOptions,
+) {
+    loop {
+        match refresh(&client, &instances, index, &options).await {
+            Ok(last_index) => {
+                if last_index <= index {
+                    sleep(options.poll_interval).await;
+                }
+                index = last_index;
+            }
+            // Keep the last known instances until the agent is reachable
+            Err(_) => sleep(options.poll_interval).await,
+        }
+    }
+}
+
+//
+// Load the instances of the service once the index passes `index`, dropping
+// any whose allocation is no longer running or whose checks are failing, see
+// `Services::healthy`. Registrations are removed when an allocation stops
+// cleanly but linger when its node is lost.
+//
+async fn refresh(
+    client: &NomadClient,
+    instances: &Instances,
+    index: u64,
+    options: &ResolverOptions,
+) -> Result<u64> {
+    let query = QueryOptions {
+        wait_index: Some(index).filter(|i| *i > 0),
+        wait_time: Some(options.wait_time),
+        ..Default::default()
+    };
+    let (mut registrations, meta) = client.services().get(&instances.name, &query).await?;
+
+    if !registrations.is_empty() {
+        let filter = registrations
+            .iter()
+            .map(|r| format!("ID == \"{}\"", r.alloc_id))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        let query = QueryOptions {
+            filter: Some(filter),
+            ..Default::default()
+        };
+        let (allocations, _) = client.allocations().list(&query).await?;
+        let running: HashSet<&str> = allocations
+            .iter()
+            .filter(|a| a.client_status == ALLOC_CLIENT_STATUS_RUNNING)
+            .map(|a| a.id.as_str())
+            .collect();
+        registrations.retain(|r| running.contains(r.alloc_id.as_str()));
+    }
+    let registrations = client.services().healthy(registrations).await?;
+
+    instances.replace(registrations);
+    Ok(meta.last_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub_server::{serve, StubResponse};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    fn registration(id: &str, address: &str, tags: &[&str]) -> ServiceRegistration {
+        ServiceRegistration {
+            id: id.to_string(),
+            service_name: "api".to_string(),
+            alloc_id: format!("alloc-{}", id),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            address: address.to_string(),
+            port: 8080,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pick_strategies() {
+        let registrations = vec![
+            registration("a", "10.0.0.1", &["http"]),
+            registration("b", "10.0.0.2", &["http"]),
+            registration("c", "10.0.0.3", &["grpc"]),
+        ];
+
+        let instances = Instances::new("api", vec!["http".to_string()], Strategy::RoundRobin);
+        assert_eq!(instances.pick(), None);
+        instances.replace(registrations.clone());
+        let picks: Vec<_> = (0..4)
+            .map(|_| instances.pick().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            picks,
+            [
+                "10.0.0.1:8080",
+                "10.0.0.2:8080",
+                "10.0.0.1:8080",
+                "10.0.0.2:8080"
+            ]
+        );
+
+        let instances = Instances::new("api", Vec::new(), Strategy::LeastRecentlyUsed);
+        instances.replace(registrations.clone());
+        let first = instances.pick().unwrap();
+        let second = instances.pick().unwrap();
+        let third = instances.pick().unwrap();
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        assert_ne!(first, third);
+        assert_eq!(instances.pick(), Some(first));
+
+        let instances = Instances::new("api", vec!["grpc".to_string()], Strategy::Random);
+        instances.replace(registrations);
+        assert_eq!(instances.pick(), Some("10.0.0.3:8080".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn resolve_healthy_instances() {
+        let address = serve(
+            |request| match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/v1/service/api") => StubResponse::json(&json!([
+                    registration("a", "10.0.0.1", &["http"]),
+                    registration("b", "10.0.0.2", &["http"]),
+                    registration("c", "10.0.0.3", &["http"]),
+                ]))
+                .index(5),
+                ("GET", "/v1/allocations") => {
+                    let filter = request.query.get("filter").expect("filter");
+                    assert!(filter.contains("ID == \"alloc-a\""));
+                    StubResponse::json(&json!([
+                        {"ID": "alloc-a", "ClientStatus": "running"},
+                        {"ID": "alloc-b", "ClientStatus": "lost"},
+                        {"ID": "alloc-c", "ClientStatus": "running"}
+                    ]))
+                }
+                ("GET", "/v1/client/allocation/alloc-a/checks") => StubResponse::json(&json!({
+                    "1": {"Service": "api", "Mode": "healthiness", "Status": "success"}
+                })),
+                ("GET", "/v1/client/allocation/alloc-c/checks") => StubResponse::json(&json!({
+                    "1": {"Service": "api", "Mode": "healthiness", "Status": "failure"}
+                })),
+                _ => StubResponse::raw(404, b"not found"),
+            },
+        )
+        .await;
+
+        let resolver = ServiceResolver::new(
+            NomadClient::new(&address),
+            ResolverOptions {
+                poll_interval: Duration::from_millis(10),
+                ..Default::default()
+            },
+        );
+        let addr = resolver.resolve("api", &["http"]).await.expect("resolve");
+        assert_eq!(addr, Some("10.0.0.1:8080".parse().unwrap()));
+
+        let mut discover = resolver.discover("api", &["http"]).await.expect("discover");
+        fn is_discover<D: tower::discover::Discover<Key = String>>(_: &D) {}
+        is_discover(&discover);
+        match discover.next().await {
+            Some(Ok(Change::Insert(id, addr))) => {
+                assert_eq!(id, "a");
+                assert_eq!(addr, "10.0.0.1:8080".parse().unwrap());
+            }
+            _ => panic!("expected an insert"),
+        }
+        drop(resolver);
+        assert!(discover.next().await.is_none());
+    }
+}
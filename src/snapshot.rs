@@ -0,0 +1,555 @@
+//
+// Offline inspection of snapshot archives saved with
+// `Operator::snapshot_save`, without restoring them into a cluster.
+//
+// An archive is a gzipped tarball holding `meta.json` (the raft metadata),
+// `state.bin` (the state store) and `SHA256SUMS`. The state store is a
+// msgpack header followed by records, each a type byte and a msgpack value.
+//
+use flate2::read::GzDecoder;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_with::rust::default_on_null;
+use sha2::{Digest, Sha256};
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::model::allocations::Allocation;
+use crate::model::jobs::Job;
+use crate::model::nodes::Node;
+
+// Record types of the state store which are decoded into this crate's models,
+// numbered as nomad's SnapshotType
+pub const SNAPSHOT_NODE: u8 = 0;
+pub const SNAPSHOT_JOB: u8 = 1;
+pub const SNAPSHOT_ALLOC: u8 = 4;
+
+// Some of the record types returned as `StateRecord::Other`
+pub const SNAPSHOT_INDEX: u8 = 2;
+pub const SNAPSHOT_EVAL: u8 = 3;
+pub const SNAPSHOT_TIME_TABLE: u8 = 5;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SnapshotServer {
+    pub suffrage: i32,
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub address: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SnapshotConfiguration {
+    #[serde(deserialize_with = "default_on_null::deserialize")]
+    pub servers: Vec<SnapshotServer>,
+}
+
+// SnapshotMeta is the raft metadata of the snapshot from `meta.json`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SnapshotMeta {
+    pub version: i32,
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub index: u64,
+    pub term: u64,
+    pub configuration: SnapshotConfiguration,
+    pub configuration_index: u64,
+    pub size: i64,
+}
+
+// A record of the state store; tables without a model in this crate are
+// kept as JSON
+#[derive(Debug, Clone)]
+pub enum StateRecord {
+    Node(Box<Node>),
+    Job(Box<Job>),
+    Allocation(Box<Allocation>),
+    Other {
+        record_type: u8,
+        value: serde_json::Value,
+    },
+}
+
+//
+// Snapshot reads an archive as a stream. The state store is decoded as it is
+// decompressed, so the records can be iterated once per snapshot.
+//
+pub struct Snapshot<R: Read> {
+    pub meta: SnapshotMeta,
+    state: State<R>,
+}
+
+impl Snapshot<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Snapshot<File>> {
+        Snapshot::from_reader(File::open(path)?)
+    }
+}
+
+impl<R: Read> Snapshot<R> {
+    //
+    // Read the archive up to the start of the state store. Nomad writes
+    // `meta.json` ahead of `state.bin` and the checksums last, so they are
+    // verified once the final record has been read; a mismatch is returned
+    // as the last item of the iteration.
+    //
+    pub fn from_reader(reader: R) -> Result<Snapshot<R>> {
+        let mut archive = Archive {
+            reader: BufReader::new(GzDecoder::new(reader)),
+            size: 0,
+        };
+        let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+        loop {
+            let (name, size) = archive
+                .next_entry()?
+                .ok_or_else(|| Error::Snapshot("archive has no state.bin".to_string()))?;
+            match name.as_str() {
+                "state.bin" => break,
+                "meta.json" | "SHA256SUMS" => {
+                    let data = archive.read_entry(size)?;
+                    files.insert(name, data);
+                }
+                _ => archive.skip_entry(size)?,
+            }
+        }
+        let meta = files
+            .get("meta.json")
+            .ok_or_else(|| Error::Snapshot("archive has no meta.json".to_string()))?;
+
+        Ok(Snapshot {
+            meta: serde_json::from_slice(meta)?,
+            state: State {
+                remaining: archive.size,
+                archive,
+                hasher: Sha256::new(),
+                files,
+            },
+        })
+    }
+
+    pub fn index(&self) -> u64 {
+        self.meta.index
+    }
+
+    pub fn term(&self) -> u64 {
+        self.meta.term
+    }
+
+    pub fn version(&self) -> i32 {
+        self.meta.version
+    }
+
+    //
+    // Iterate the records of the state store. A record which does not decode
+    // into its model is returned as an error and iteration continues; an
+    // error in the msgpack framing ends the iteration.
+    //
+    pub fn records(self) -> impl Iterator<Item = Result<StateRecord>> {
+        self.raw_records().map(|raw| {
+            let (record_type, value) = raw?;
+            Ok(match record_type {
+                SNAPSHOT_NODE => StateRecord::Node(Box::new(decode(value, "node")?)),
+                SNAPSHOT_JOB => StateRecord::Job(Box::new(decode(value, "job")?)),
+                SNAPSHOT_ALLOC => StateRecord::Allocation(Box::new(decode(value, "allocation")?)),
+                record_type => StateRecord::Other { record_type, value },
+            })
+        })
+    }
+
+    pub fn jobs(self) -> impl Iterator<Item = Result<Job>> {
+        self.records_of(SNAPSHOT_JOB, "job")
+    }
+
+    pub fn nodes(self) -> impl Iterator<Item = Result<Node>> {
+        self.records_of(SNAPSHOT_NODE, "node")
+    }
+
+    pub fn allocations(self) -> impl Iterator<Item = Result<Allocation>> {
+        self.records_of(SNAPSHOT_ALLOC, "allocation")
+    }
+
+    fn records_of<T: DeserializeOwned>(
+        self,
+        wanted: u8,
+        what: &'static str,
+    ) -> impl Iterator<Item = Result<T>> {
+        self.raw_records().filter_map(move |raw| match raw {
+            Ok((record_type, value)) if record_type == wanted => Some(decode(value, what)),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+    }
+
+    fn raw_records(self) -> RawRecords<R> {
+        RawRecords {
+            state: self.state,
+            started: false,
+            done: false,
+        }
+    }
+}
+
+// Archive reads the entries of the tarball in order
+struct Archive<R: Read> {
+    reader: BufReader<GzDecoder<R>>,
+    // Size of the entry being read
+    size: u64,
+}
+
+impl<R: Read> Archive<R> {
+    // The name and size of the next entry, `None` at the end of the archive
+    fn next_entry(&mut self) -> Result<Option<(String, u64)>> {
+        let mut header = tar::Header::new_old();
+        self.reader
+            .read_exact(header.as_mut_bytes())
+            .map_err(truncated)?;
+        if header.as_bytes().iter().all(|b| *b == 0) {
+            return Ok(None);
+        }
+        self.size = header.entry_size()?;
+        let name = header.path()?.to_string_lossy().into_owned();
+        Ok(Some((name, self.size)))
+    }
+
+    fn read_entry(&mut self, size: u64) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        (&mut self.reader).take(size).read_to_end(&mut data)?;
+        if data.len() as u64 != size {
+            return Err(truncated(io::ErrorKind::UnexpectedEof.into()));
+        }
+        self.skip_padding(size)?;
+        Ok(data)
+    }
+
+    fn skip_entry(&mut self, size: u64) -> Result<()> {
+        if io::copy(&mut (&mut self.reader).take(size), &mut io::sink())? != size {
+            return Err(truncated(io::ErrorKind::UnexpectedEof.into()));
+        }
+        self.skip_padding(size)
+    }
+
+    // Entries are padded to the 512 byte blocks of the archive
+    fn skip_padding(&mut self, size: u64) -> Result<()> {
+        let mut padding = [0u8; 512];
+        let padding = &mut padding[..((512 - size % 512) % 512) as usize];
+        self.reader.read_exact(padding).map_err(truncated)
+    }
+}
+
+fn truncated(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::Snapshot("archive is truncated".to_string()),
+        _ => Error::Io(e),
+    }
+}
+
+// State reads the `state.bin` entry, hashing it for the final verification
+struct State<R: Read> {
+    archive: Archive<R>,
+    remaining: u64,
+    hasher: Sha256,
+    // `meta.json` and `SHA256SUMS` as they are read from the archive
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl<R: Read> State<R> {
+    // Read the rest of the archive and check the checksums of its files
+    fn verify(&mut self) -> Result<()> {
+        if self.remaining != 0 {
+            return Err(truncated(io::ErrorKind::UnexpectedEof.into()));
+        }
+        self.archive.skip_padding(self.archive.size)?;
+        while let Some((name, size)) = self.archive.next_entry()? {
+            if name == "SHA256SUMS" {
+                let data = self.archive.read_entry(size)?;
+                self.files.insert(name, data);
+            } else {
+                self.archive.skip_entry(size)?;
+            }
+        }
+        let sums = self
+            .files
+            .get("SHA256SUMS")
+            .ok_or_else(|| Error::Snapshot("archive has no SHA256SUMS".to_string()))?;
+        let meta = Sha256::digest(&self.files["meta.json"]);
+        let state = std::mem::take(&mut self.hasher).finalize();
+        verify(sums, &[("meta.json", &meta), ("state.bin", &state)])
+    }
+}
+
+impl<R: Read> Read for State<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = buf
+            .len()
+            .min(self.remaining.try_into().unwrap_or(usize::MAX));
+        let n = self.archive.reader.read(&mut buf[..max])?;
+        self.hasher.update(&buf[..n]);
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+// RawRecords yields the type and value of each record of the state store
+struct RawRecords<R: Read> {
+    state: State<R>,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> RawRecords<R> {
+    fn read_value(&mut self) -> Result<serde_json::Value> {
+        let value = rmpv::decode::read_value(&mut self.state)
+            .map_err(|e| Error::Snapshot(format!("malformed state store: {}", e)))?;
+        Ok(to_json(value))
+    }
+
+    fn next_record(&mut self) -> Result<Option<(u8, serde_json::Value)>> {
+        if !self.started {
+            self.started = true;
+            self.read_value()?;
+        }
+        let mut record_type = [0u8; 1];
+        if self.state.read(&mut record_type)? == 0 {
+            self.state.verify()?;
+            return Ok(None);
+        }
+        Ok(Some((record_type[0], self.read_value()?)))
+    }
+}
+
+impl<R: Read> Iterator for RawRecords<R> {
+    type Item = Result<(u8, serde_json::Value)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.next_record().transpose();
+        self.done = !matches!(record, Some(Ok(_)));
+        record
+    }
+}
+
+fn decode<T: DeserializeOwned>(value: serde_json::Value, what: &str) -> Result<T> {
+    serde_json::from_value(value).map_err(|e| {
+        Error::Json(serde::de::Error::custom(format!(
+            "{} record does not match model: {}",
+            what, e
+        )))
+    })
+}
+
+// Check the digests of files against the "<sha256 hex>  <name>" lines of
+// SHA256SUMS
+fn verify(sums: &[u8], files: &[(&str, &[u8])]) -> Result<()> {
+    let sums = String::from_utf8_lossy(sums);
+    for (name, digest) in files {
+        let expected = sums
+            .lines()
+            .filter_map(|line| line.split_once(char::is_whitespace))
+            .find(|(_, file)| file.trim() == *name)
+            .map(|(sum, _)| sum.to_lowercase())
+            .ok_or_else(|| Error::Snapshot(format!("no checksum for {}", name)))?;
+        let actual: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        if actual != expected {
+            return Err(Error::Integrity(format!(
+                "{} has sha256 {} expected {}",
+                name, actual, expected
+            )));
+        }
+    }
+    Ok(())
+}
+
+//
+// Convert a msgpack value to JSON so the serde models can be reused. Binary
+// becomes base64 as in the HTTP API and msgpack timestamps become RFC 3339.
+//
+fn to_json(value: rmpv::Value) -> serde_json::Value {
+    use rmpv::Value;
+    use serde_json::Value as Json;
+
+    match value {
+        Value::Nil => Json::Null,
+        Value::Boolean(b) => Json::Bool(b),
+        Value::Integer(i) => match (i.as_u64(), i.as_i64()) {
+            (Some(u), _) => Json::from(u),
+            (_, Some(i)) => Json::from(i),
+            _ => Json::Null,
+        },
+        Value::F32(f) => Json::from(f64::from(f)),
+        Value::F64(f) => Json::from(f),
+        Value::String(s) => Json::String(s.into_str().unwrap_or_default()),
+        Value::Binary(b) => Json::String(base64::encode(b)),
+        Value::Array(items) => Json::Array(items.into_iter().map(to_json).collect()),
+        Value::Map(entries) => Json::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        Value::String(s) => s.into_str().unwrap_or_default(),
+                        other => other.to_string(),
+                    };
+                    (key, to_json(v))
+                })
+                .collect(),
+        ),
+        Value::Ext(-1, data) => timestamp(&data)
+            .map(|t| Json::String(t.to_rfc3339()))
+            .unwrap_or(Json::Null),
+        Value::Ext(_, _) => Json::Null,
+    }
+}
+
+// Decode the msgpack timestamp extension
+fn timestamp(data: &[u8]) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::TimeZone;
+
+    let (secs, nanos) = match data.len() {
+        4 => (i64::from(u32::from_be_bytes(data.try_into().ok()?)), 0),
+        8 => {
+            let value = u64::from_be_bytes(data.try_into().ok()?);
+            ((value & 0x3_ffff_ffff) as i64, (value >> 34) as u32)
+        }
+        12 => (
+            i64::from_be_bytes(data[4..].try_into().ok()?),
+            u32::from_be_bytes(data[..4].try_into().ok()?),
+        ),
+        _ => return None,
+    };
+    chrono::Utc.timestamp_opt(secs, nanos).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use rmpv::Value;
+
+    fn map(entries: &[(&str, Value)]) -> Value {
+        Value::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (Value::from(*k), v.clone()))
+                .collect(),
+        )
+    }
+
+    fn archive(state: &[u8], corrupt: bool) -> Vec<u8> {
+        let meta = br#"{"Version":1,"ID":"2-120-1612345678","Index":120,"Term":2,"Configuration":{"Servers":[{"Suffrage":0,"ID":"a","Address":"10.0.0.1:4647"}]},"ConfigurationIndex":1,"Size":100}"#;
+        let hex = |data: &[u8]| -> String {
+            Sha256::digest(data)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect()
+        };
+        let state_sum = if corrupt { hex(b"other") } else { hex(state) };
+        let sums = format!("{}  meta.json\n{}  state.bin\n", hex(meta), state_sum);
+
+        let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, data) in &[
+            ("meta.json", &meta[..]),
+            ("state.bin", state),
+            ("SHA256SUMS", sums.as_bytes()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            header.set_cksum();
+            tar.append_data(&mut header, name, *data).expect("append");
+        }
+        tar.into_inner().expect("tar").finish().expect("gzip")
+    }
+
+    fn state() -> Vec<u8> {
+        let mut state = Vec::new();
+        let mut write = |record_type: Option<u8>, value: Value| {
+            if let Some(record_type) = record_type {
+                state.push(record_type);
+            }
+            rmpv::encode::write_value(&mut state, &value).expect("encode");
+        };
+        write(None, map(&[]));
+        write(
+            Some(SNAPSHOT_TIME_TABLE),
+            Value::Array(vec![map(&[
+                ("Index", 1.into()),
+                ("Time", Value::Ext(-1, vec![0, 0, 0, 1])),
+            ])]),
+        );
+        // A node missing required fields
+        write(Some(SNAPSHOT_NODE), map(&[("ID", "node-1".into())]));
+        write(
+            Some(SNAPSHOT_JOB),
+            map(&[
+                ("ID", "example".into()),
+                ("Name", "example".into()),
+                ("Type", "service".into()),
+                ("Payload", Value::Binary(b"hi".to_vec())),
+            ]),
+        );
+        write(
+            Some(SNAPSHOT_INDEX),
+            map(&[("Key", "jobs".into()), ("Value", 120.into())]),
+        );
+        write(
+            Some(SNAPSHOT_ALLOC),
+            map(&[("ID", "alloc-1".into()), ("JobID", "example".into())]),
+        );
+        state
+    }
+
+    #[test]
+    fn inspect_snapshot() {
+        let archive = archive(&state(), false);
+        let open = || Snapshot::from_reader(&archive[..]).expect("open");
+        let snapshot = open();
+        assert_eq!(snapshot.index(), 120);
+        assert_eq!(snapshot.term(), 2);
+        assert_eq!(snapshot.version(), 1);
+        assert_eq!(
+            snapshot.meta.configuration.servers[0].address,
+            "10.0.0.1:4647"
+        );
+
+        let jobs: Vec<Job> = snapshot.jobs().collect::<Result<_>>().expect("jobs");
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].payload, b"hi");
+
+        let allocs: Vec<Allocation> = open().allocations().collect::<Result<_>>().expect("allocs");
+        assert_eq!(allocs[0].job_id, "example");
+
+        // The node does not decode but iteration continues past it
+        let nodes: Vec<Result<Node>> = open().nodes().collect();
+        assert_eq!(nodes.len(), 1);
+        assert!(nodes[0].is_err());
+
+        let records: Vec<_> = open().records().collect();
+        assert_eq!(records.len(), 5);
+        match records[0] {
+            Ok(StateRecord::Other {
+                record_type: SNAPSHOT_TIME_TABLE,
+                ref value,
+            }) => {
+                assert_eq!(value[0]["Time"], "1970-01-01T00:00:01+00:00")
+            }
+            ref other => panic!("unexpected record {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_checksum_mismatch() {
+        let archive = archive(&state(), true);
+        let snapshot = Snapshot::from_reader(&archive[..]).expect("open");
+        let records: Vec<_> = snapshot.records().collect();
+        assert_eq!(records.len(), 6);
+        assert!(matches!(records.last(), Some(Err(Error::Integrity(_)))));
+    }
+}